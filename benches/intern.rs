@@ -0,0 +1,59 @@
+//! Compare the memory held by a parsed config with and without interned key
+//! names. Run with `cargo bench --bench intern`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ini::{Ini, ParseOptions};
+
+const SECTIONS: usize = 1_000;
+const KEYS: usize = 20;
+
+/// An allocator that tracks the number of bytes currently allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let mut text = String::new();
+    for section in 0..SECTIONS {
+        text.push_str(&format!("[section{section}]\n"));
+        for key in 0..KEYS {
+            text.push_str(&format!("setting_name_{key}=value{key}\n"));
+        }
+    }
+
+    let plain = retained(&text, &ParseOptions::new());
+    let interned = retained(&text, &ParseOptions::new().intern_names(true));
+    assert!(interned < plain);
+
+    println!("{SECTIONS} sections of {KEYS} keys, bytes held by the parsed config");
+    println!("without interning: {plain}");
+    println!("with interning:    {interned}");
+}
+
+/// Parse a config and return the number of bytes it holds once parsed.
+fn retained(text: &str, options: &ParseOptions) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let ini = Ini::from_str_with(text, options).unwrap();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+    drop(ini);
+    after - before
+}
@@ -0,0 +1,53 @@
+//! Compare an incremental reparse after a one-character edit with a full
+//! parse of the same text. Run with `cargo bench --bench reparse`.
+
+use std::time::{Duration, Instant};
+
+use ini::{Ini, ParseOptions};
+
+const SECTIONS: usize = 1_000;
+const KEYS: usize = 20;
+const ROUNDS: u32 = 50;
+
+fn main() {
+    let mut text = String::new();
+    for section in 0..SECTIONS {
+        text.push_str(&format!("[section{section}]\n"));
+        for key in 0..KEYS {
+            text.push_str(&format!("key{key}=value{key}\n"));
+        }
+    }
+    let options = ParseOptions::new();
+    let edit = text.find("[section500]").unwrap() + "[section500]\nkey0=value".len();
+
+    let full = time(|| {
+        Ini::from_str_with(&text, &options).unwrap();
+    });
+
+    let mut ini = Ini::from_str_with(&text, &options).unwrap();
+    let mut edited = text.clone();
+    let incremental = time(|| {
+        let digit = if edited.as_bytes()[edit] == b'0' {
+            "1"
+        } else {
+            "0"
+        };
+        edited.replace_range(edit..edit + 1, digit);
+        ini.reparse_range_with(&edited, edit..edit + 1, &options)
+            .unwrap();
+    });
+    assert_eq!(ini, Ini::from_str_with(&edited, &options).unwrap());
+
+    println!("{SECTIONS} sections of {KEYS} keys, mean of {ROUNDS} rounds");
+    println!("full parse:         {full:?}");
+    println!("incremental reparse: {incremental:?}");
+}
+
+/// Run a function several times and return the mean duration.
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
@@ -0,0 +1,40 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::Section;
+
+impl Section {
+    /// Get the value of a key decoded from base64.
+    ///
+    /// The value must use the standard alphabet with padding. Returns `None`
+    /// if the key does not exist or the value is not valid base64.
+    pub fn get_base64(&self, name: &str) -> Option<Vec<u8>> {
+        STANDARD.decode(self.get_str(name)?).ok()
+    }
+
+    /// Insert a key with binary data encoded as base64.
+    ///
+    /// If a key exists with the same name, it is overwritten.
+    pub fn set_base64(&mut self, name: &str, data: &[u8]) {
+        self.insert(name.into(), STANDARD.encode(data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ini;
+
+    #[test]
+    fn base64_round_trip() {
+        let mut ini = Ini::new();
+        ini[""].set_base64("key", &[0, 1, 254, 255]);
+        let ini = Ini::from_str(&ini.to_string()).unwrap();
+        assert_eq!(ini[""].get_base64("key"), Some(vec![0, 1, 254, 255]));
+    }
+
+    #[test]
+    fn base64_invalid() {
+        let ini = Ini::from_str("key=not-base64").unwrap();
+        assert_eq!(ini[""].get_base64("key"), None);
+        assert_eq!(ini[""].get_base64("missing"), None);
+    }
+}
@@ -0,0 +1,465 @@
+use std::vec;
+
+use serde::{
+    de::{
+        self, value::BorrowedStrDeserializer, DeserializeOwned, DeserializeSeed, MapAccess,
+        SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+
+use crate::{
+    typed::{parse_bool, parse_int},
+    Error, Ini, Result, Section,
+};
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Deserialize(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingKey(field.into())
+    }
+}
+
+impl Ini {
+    /// Deserialize the config into a type that implements `Deserialize`.
+    ///
+    /// Sections are read as fields of the type and keys as fields of the
+    /// sections, so a struct with one field per section, each a struct with
+    /// one field per key, mirrors the config. Keys in the default section are
+    /// read as fields of the type itself. Values are parsed from their text
+    /// as the field type requires, with booleans and integers read as by
+    /// `Section::get_bool` and `Section::get_int`. A sequence, such as a
+    /// `Vec`, is read from every value of a key, as returned by
+    /// `Section::get_all`, so `path[]=lib` and `path[]=bin` read as
+    /// `["lib", "bin"]`.
+    ///
+    /// Returns `Error::MissingKey` with a path of the form `section.key` if a
+    /// required field is absent, and `Error::Type` with such a path if a
+    /// value fails to parse. Sections and keys that the type does not read
+    /// are ignored.
+    ///
+    /// ```
+    /// use ini::Ini;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     server: Server,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     port: u16,
+    /// }
+    ///
+    /// let ini = Ini::from_str("[server]\nport=8080").unwrap();
+    /// let config: Config = ini.deserialize_into().unwrap();
+    /// assert_eq!(config.server.port, 8080);
+    /// ```
+    pub fn deserialize_into<T: DeserializeOwned>(&self) -> Result<T> {
+        T::deserialize(IniDeserializer(self))
+    }
+}
+
+/// Deserializer over a whole config, as a map of default keys and sections.
+struct IniDeserializer<'a>(&'a Ini);
+
+impl<'de> Deserializer<'de> for IniDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let default = self.0.section("");
+        visitor.visit_map(IniAccess {
+            keys: default
+                .into_iter()
+                .flat_map(Section::iter)
+                .collect::<Vec<_>>()
+                .into_iter(),
+            sections: self
+                .0
+                .sections()
+                .filter(|(name, _)| !name.is_empty())
+                .collect::<Vec<_>>()
+                .into_iter(),
+            default,
+            next: Next::None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The entry whose value is read next.
+enum Next<'a> {
+    None,
+    Key(&'a str),
+    Section(&'a str, &'a Section),
+}
+
+struct IniAccess<'a> {
+    keys: vec::IntoIter<(&'a str, &'a str)>,
+    sections: vec::IntoIter<(&'a str, &'a Section)>,
+    default: Option<&'a Section>,
+    next: Next<'a>,
+}
+
+impl<'de> MapAccess<'de> for IniAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let name = if let Some((key, _)) = self.keys.next() {
+            self.next = Next::Key(key);
+            key
+        } else if let Some((name, section)) = self.sections.next() {
+            self.next = Next::Section(name, section);
+            name
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match std::mem::replace(&mut self.next, Next::None) {
+            Next::Key(key) => seed.deserialize(ValueDeserializer::new(
+                self.default
+                    .expect("default keys come from the default section"),
+                key,
+                key.into(),
+            )),
+            Next::Section(name, section) => seed
+                .deserialize(SectionDeserializer(section, name))
+                .map_err(|error| match error {
+                    Error::MissingKey(key) => Error::MissingKey(format!("{name}.{key}")),
+                    error => error,
+                }),
+            Next::None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+}
+
+/// Deserializer over one section, as a map of keys.
+struct SectionDeserializer<'a>(&'a Section, &'a str);
+
+impl<'de> Deserializer<'de> for SectionDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(SectionAccess {
+            section: self.0,
+            name: self.1,
+            keys: self.0.keys().collect::<Vec<_>>().into_iter(),
+            next: None,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct SectionAccess<'a> {
+    section: &'a Section,
+    name: &'a str,
+    keys: vec::IntoIter<&'a str>,
+    next: Option<&'a str>,
+}
+
+impl<'de> MapAccess<'de> for SectionAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some(key) = self.keys.next() else {
+            return Ok(None);
+        };
+        self.next = Some(key);
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let key = self
+            .next
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        let path = format!("{}.{key}", self.name);
+        seed.deserialize(ValueDeserializer::new(self.section, key, path))
+    }
+}
+
+/// Deserializer over a value of one key, parsed from its text.
+///
+/// A sequence is read from every value of the key, as returned by
+/// `Section::get_all`, and anything else from a single value.
+struct ValueDeserializer<'a> {
+    section: &'a Section,
+    key: &'a str,
+    value: &'a str,
+    path: String,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    /// Create a deserializer over the last value of a key.
+    fn new(section: &'a Section, key: &'a str, path: String) -> Self {
+        let value = section.get_str(key).unwrap_or_default();
+        ValueDeserializer {
+            section,
+            key,
+            value,
+            path,
+        }
+    }
+
+    fn value(&self) -> &'a str {
+        self.value
+    }
+
+    fn parse<T: std::str::FromStr>(&self) -> Result<T> {
+        self.value()
+            .parse()
+            .map_err(|_| Error::Type(self.path.clone()))
+    }
+
+    fn parse_int<T: std::str::FromStr + TryFrom<i64>>(&self) -> Result<T> {
+        self.parse().or_else(|_| {
+            parse_int(self.value())
+                .and_then(|value| T::try_from(value).ok())
+                .ok_or_else(|| Error::Type(self.path.clone()))
+        })
+    }
+}
+
+macro_rules! deserialize_int {
+    ($($method:ident $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.parse_int()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.value())
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value = parse_bool(self.value).ok_or_else(|| Error::Type(self.path.clone()))?;
+        visitor.visit_bool(value)
+    }
+
+    deserialize_int! {
+        deserialize_i8 visit_i8,
+        deserialize_i16 visit_i16,
+        deserialize_i32 visit_i32,
+        deserialize_i64 visit_i64,
+        deserialize_i128 visit_i128,
+        deserialize_u8 visit_u8,
+        deserialize_u16 visit_u16,
+        deserialize_u32 visit_u32,
+        deserialize_u64 visit_u64,
+        deserialize_u128 visit_u128,
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.parse()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.parse()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_char(self.parse()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.value()))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(ValuesAccess {
+            values: self.section.get_all(self.key).into_iter(),
+            deserializer: self,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+/// Access to every value of a key as a sequence.
+struct ValuesAccess<'a> {
+    values: vec::IntoIter<&'a String>,
+    deserializer: ValueDeserializer<'a>,
+}
+
+impl<'de> SeqAccess<'de> for ValuesAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        let Some(value) = self.values.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(ValueDeserializer {
+            value,
+            path: self.deserializer.path.clone(),
+            ..self.deserializer
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+        server: Server,
+        database: Database,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Database {
+        user: String,
+        port: u16,
+        timeout: Option<u16>,
+        #[serde(default)]
+        readonly: bool,
+    }
+
+    #[test]
+    fn deserialize_into() {
+        let ini = Ini::from_str(
+            "name=app\n[server]\nhost=\"0.0.0.0\"\nport=8080\nextra=1\n[database]\nuser=admin\nport=0x1538\nreadonly=yes\n[unused]",
+        )
+        .unwrap();
+        let config: Config = ini.deserialize_into().unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "app".into(),
+                server: Server {
+                    host: "0.0.0.0".into(),
+                    port: 8080,
+                },
+                database: Database {
+                    user: "admin".into(),
+                    port: 5432,
+                    timeout: None,
+                    readonly: true,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_into_errors() {
+        let ini = Ini::from_str("name=app\n[server]\nhost=h\n[database]\nuser=u\nport=1").unwrap();
+        assert_eq!(
+            ini.deserialize_into::<Config>(),
+            Err(Error::MissingKey("server.port".into()))
+        );
+
+        let ini = Ini::from_str("name=app\n[database]\nuser=u\nport=1").unwrap();
+        assert_eq!(
+            ini.deserialize_into::<Config>(),
+            Err(Error::MissingKey("server".into()))
+        );
+
+        let ini = Ini::from_str("name=app\n[server]\nhost=h\nport=70000").unwrap();
+        assert_eq!(
+            ini.deserialize_into::<Config>(),
+            Err(Error::Type("server.port".into()))
+        );
+
+        let ini = Ini::from_str(
+            "name=app\n[server]\nhost=h\nport=1\n[database]\nuser=u\nport=1\nreadonly=maybe",
+        )
+        .unwrap();
+        assert_eq!(
+            ini.deserialize_into::<Config>(),
+            Err(Error::Type("database.readonly".into()))
+        );
+    }
+
+    #[test]
+    fn deserialize_into_seq() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Build {
+            path: Vec<String>,
+            ports: Vec<u16>,
+            single: Vec<u16>,
+        }
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Config {
+            build: Build,
+        }
+
+        let text = "[build]\npath[]=lib\npath[]=bin\nports[]=80\nports[]=0x1bb\nsingle=1";
+        let config: Config = Ini::from_str(text).unwrap().deserialize_into().unwrap();
+        let build = Build {
+            path: vec!["lib".into(), "bin".into()],
+            ports: vec![80, 443],
+            single: vec![1],
+        };
+        assert_eq!(config, Config { build });
+
+        let ini = Ini::from_str("[build]\npath=a\nports[]=1\nports[]=x\nsingle=1").unwrap();
+        assert_eq!(
+            ini.deserialize_into::<Config>(),
+            Err(Error::Type("build.ports".into()))
+        );
+    }
+}
@@ -0,0 +1,32 @@
+/// Non-fatal issue found while parsing.
+///
+/// Diagnostics describe text that parses successfully but may indicate a
+/// style problem or a mistake. They are collected by
+/// `Ini::from_str_with_diagnostics` and do not affect the parsed config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The kind of issue.
+    pub kind: DiagnosticKind,
+    /// The line where the issue starts, starting at 1.
+    pub line: usize,
+    /// The column where the issue starts, starting at 1.
+    pub column: usize,
+}
+
+/// Kind of non-fatal issue found while parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// Whitespace at the end of a line that has other content, such as after
+    /// a section header or a value.
+    TrailingWhitespace,
+    /// A section header that repeats an earlier header. The keys of both are
+    /// merged into one section. The diagnostic points at the repeated header.
+    DuplicateSection {
+        /// The name of the section.
+        name: String,
+        /// The line of the first header.
+        first_line: usize,
+        /// The column of the first header.
+        first_column: usize,
+    },
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::Ini;
+
+/// Report of keys whose values differ between configs, created by
+/// `Ini::drift`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DriftReport {
+    entries: Vec<Drift>,
+}
+
+/// A key whose value differs between configs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    /// The name of the section.
+    pub section: String,
+    /// The name of the key.
+    pub key: String,
+    /// The value in each config, by config name, in the order the configs
+    /// were given. The value is `None` if the key is missing from a config.
+    pub values: Vec<(String, Option<String>)>,
+}
+
+impl Ini {
+    /// Compare several named configs and report the keys that differ.
+    ///
+    /// A key is reported if it does not have the same value in every config,
+    /// including when it is missing from some of them. Entries are sorted by
+    /// section and then by key.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let dev = Ini::from_str("[db]\nhost=localhost\nport=5432").unwrap();
+    /// let prod = Ini::from_str("[db]\nhost=db.internal\nport=5432").unwrap();
+    ///
+    /// let report = Ini::drift(&[("dev", &dev), ("prod", &prod)]);
+    /// assert_eq!(report.to_string(), "db.host: dev=localhost, prod=db.internal\n");
+    /// ```
+    pub fn drift(configs: &[(&str, &Ini)]) -> DriftReport {
+        let mut keys = BTreeSet::new();
+        for (_, ini) in configs {
+            for (section, keys_in_section) in ini.sections() {
+                for (key, _) in keys_in_section.iter() {
+                    keys.insert((section, key));
+                }
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (section, key) in keys {
+            let values: Vec<_> = configs
+                .iter()
+                .map(|(name, ini)| {
+                    let value = ini.section(section).and_then(|s| s.get_str(key));
+                    (name.to_string(), value.map(String::from))
+                })
+                .collect();
+            if values.windows(2).any(|pair| pair[0].1 != pair[1].1) {
+                entries.push(Drift {
+                    section: section.into(),
+                    key: key.into(),
+                    values,
+                });
+            }
+        }
+
+        DriftReport { entries }
+    }
+}
+
+impl DriftReport {
+    /// Iterate over the keys that differ.
+    pub fn iter(&self) -> impl Iterator<Item = &Drift> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of keys that differ.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether all configs agree.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a DriftReport {
+    type Item = &'a Drift;
+    type IntoIter = std::slice::Iter<'a, Drift>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl Display for DriftReport {
+    /// Write one line per key, listing its value in each config. Keys in the
+    /// default section are written without a section prefix, and missing
+    /// keys are written as `<missing>`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for drift in &self.entries {
+            if !drift.section.is_empty() {
+                write!(f, "{}.", drift.section)?;
+            }
+            write!(f, "{}:", drift.key)?;
+            for (ix, (name, value)) in drift.values.iter().enumerate() {
+                let separator = if ix == 0 { " " } else { ", " };
+                let value = value.as_deref().unwrap_or("<missing>");
+                write!(f, "{separator}{name}={value}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift() {
+        let dev = Ini::from_str("level=debug\n[db]\nport=1\nhost=a").unwrap();
+        let staging = Ini::from_str("level=info\n[db]\nport=1\nhost=a").unwrap();
+        let prod = Ini::from_str("level=info\n[db]\nport=1").unwrap();
+        let report = Ini::drift(&[("dev", &dev), ("staging", &staging), ("prod", &prod)]);
+        assert_eq!(report.len(), 2);
+        let keys: Vec<_> = report
+            .iter()
+            .map(|drift| (drift.section.as_str(), drift.key.as_str()))
+            .collect();
+        assert_eq!(keys, [("", "level"), ("db", "host")]);
+        assert_eq!(
+            report.to_string(),
+            "level: dev=debug, staging=info, prod=info\n\
+             db.host: dev=a, staging=a, prod=<missing>\n"
+        );
+    }
+
+    #[test]
+    fn no_drift() {
+        let ini = Ini::from_str("[db]\nport=1").unwrap();
+        let report = Ini::drift(&[("a", &ini), ("b", &ini.clone())]);
+        assert!(report.is_empty());
+    }
+}
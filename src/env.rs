@@ -0,0 +1,174 @@
+use crate::{
+    error::{Error, Result},
+    Ini,
+};
+
+impl Ini {
+    /// Render the config as shell `export` statements.
+    ///
+    /// Each key produces one line of the form `export PREFIX_SECTION_KEY=value`.
+    /// Keys in the default section omit the section component, and an empty
+    /// prefix omits the prefix component. Lines are sorted by section and then
+    /// by key.
+    ///
+    /// Names are sanitized for use as variable names: ASCII letters are
+    /// uppercased, every other character that is not an ASCII digit is
+    /// replaced with `_`, and a leading `_` is added if the name would
+    /// otherwise start with a digit.
+    ///
+    /// Values are wrapped in single quotes, so the shell does not expand them.
+    /// Embedded single quotes are written as `'\''`.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("
+    ///     [db]
+    ///     host=localhost
+    /// ").unwrap();
+    ///
+    /// assert_eq!(ini.to_env_exports("app"), "export APP_DB_HOST='localhost'\n");
+    /// ```
+    pub fn to_env_exports(&self, prefix: &str) -> String {
+        let mut lines = Vec::new();
+        for (section, keys) in self.sections() {
+            for (key, value) in keys.iter() {
+                let name = [prefix, section, key]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("_");
+                lines.push((section, key, env_name(&name), shell_quote(value)));
+            }
+        }
+        lines.sort();
+
+        let mut out = String::new();
+        for (_, _, name, value) in lines {
+            out.push_str(&format!("export {name}={value}\n"));
+        }
+        out
+    }
+}
+
+impl Ini {
+    /// Replace `${VAR}` references in values with environment variables.
+    ///
+    /// Each reference is replaced with the value of the variable, read with
+    /// `std::env::var`. If a variable is not set, or is not valid Unicode,
+    /// this fails with `Error::UnsetVariable` when `strict` is true, and the
+    /// reference is replaced with an empty string otherwise. On failure, the
+    /// config is left unchanged.
+    ///
+    /// Write `$${` for a literal `${`, so `$${HOME}` expands to `${HOME}`. A
+    /// `${` without a closing `}` is kept as is.
+    pub fn expand_env(&mut self, strict: bool) -> Result<()> {
+        let mut expanded = self.clone();
+        for section in expanded.sections_mut() {
+            for value in section.values_mut() {
+                *value = expand(value, |name| std::env::var(name).ok(), strict)?;
+            }
+        }
+        *self = expanded;
+        Ok(())
+    }
+}
+
+/// Replace the variable references in a value.
+fn expand(value: &str, lookup: impl Fn(&str) -> Option<String>, strict: bool) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(ix) = rest.find("${") {
+        if rest[..ix].ends_with('$') {
+            out.push_str(&rest[..ix]);
+            out.push('{');
+            rest = &rest[ix + 2..];
+            continue;
+        }
+        out.push_str(&rest[..ix]);
+        let Some(len) = rest[ix + 2..].find('}') else {
+            rest = &rest[ix..];
+            break;
+        };
+        let name = &rest[ix + 2..ix + 2 + len];
+        match lookup(name) {
+            Some(value) => out.push_str(&value),
+            None if strict => return Err(Error::UnsetVariable(name.into())),
+            None => {}
+        }
+        rest = &rest[ix + 3 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Convert a name into a valid environment variable name.
+fn env_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Quote a value so that the shell reads it literally.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_env_exports() {
+        let ini = Ini::from_str(
+            r#"
+            level=debug
+            [db]
+            host=localhost
+            "pass word"="it's"
+            "#,
+        )
+        .unwrap();
+        let exports = ini.to_env_exports("app");
+        let expected = "export APP_LEVEL='debug'\n\
+                        export APP_DB_HOST='localhost'\n\
+                        export APP_DB_PASS_WORD='it'\\''s'\n";
+        assert_eq!(exports, expected);
+    }
+
+    #[test]
+    fn expand_env() {
+        std::env::set_var("INI_EXPAND_ENV_SET", "/data");
+        let text = r#"
+            [paths]
+            data="${INI_EXPAND_ENV_SET}/files"
+            cache="${INI_EXPAND_ENV_UNSET}/cache"
+            literal="$${INI_EXPAND_ENV_SET}"
+            open="${INI_EXPAND_ENV_SET"
+            "#;
+        let mut ini = Ini::from_str(text).unwrap();
+        let error = Error::UnsetVariable("INI_EXPAND_ENV_UNSET".into());
+        assert_eq!(ini.expand_env(true), Err(error));
+        assert_eq!(ini["paths"]["data"], "${INI_EXPAND_ENV_SET}/files");
+
+        ini.expand_env(false).unwrap();
+        assert_eq!(ini["paths"]["data"], "/data/files");
+        assert_eq!(ini["paths"]["cache"], "/cache");
+        assert_eq!(ini["paths"]["literal"], "${INI_EXPAND_ENV_SET}");
+        assert_eq!(ini["paths"]["open"], "${INI_EXPAND_ENV_SET");
+    }
+
+    #[test]
+    fn to_env_exports_no_prefix() {
+        let ini = Ini::from_str("[1st]\nkey=value").unwrap();
+        assert_eq!(ini.to_env_exports(""), "export _1ST_KEY='value'\n");
+    }
+}
@@ -1,8 +1,301 @@
+use std::fmt::{self, Display, Formatter};
+
 /// Error type for INI operations.
-#[derive(PartialEq, Debug)]
+#[derive(Debug)]
 pub enum Error {
-    Parse,
+    /// The text is not valid INI, with the position of the offending token.
+    Parse { line: usize, column: usize },
+    /// A section inherits from a parent that has not been declared.
+    UndefinedParent(String),
+    /// A case-insensitive lookup matched more than one section.
+    AmbiguousSection(String),
+    /// A case-insensitive lookup matched more than one key.
+    AmbiguousKey(String),
+    /// A key does not exist.
+    MissingKey(String),
+    /// Several keys do not exist.
+    MissingKeys(Vec<String>),
+    /// The value of a key cannot be converted to the requested type.
+    Type(String),
+    /// A tab was used as whitespace while tabs are disallowed.
+    Tab { line: usize, column: usize },
+    /// A key has an empty value while empty values are disallowed.
+    EmptyValue(String),
+    /// A dotted name has more parts than allowed.
+    NameDepth(String),
+    /// A value is followed by another `=`, as in `a=b=c`.
+    UnexpectedEqual { line: usize, column: usize },
+    /// A key uses a name reserved by the parse options.
+    ReservedName(String),
+    /// A section is not read by the type being loaded.
+    UnknownSection(String),
+    /// A key is not read by the type being loaded.
+    UnknownKey { section: String, key: String },
+    /// Several errors occurred.
+    Multiple(Vec<Error>),
+    /// A name starts with `.` while such names are rejected.
+    LeadingDot(String),
+    /// A file or directory could not be read or written.
+    Io(std::io::Error),
+    /// A key is declared more than once in a section while repeated keys are
+    /// rejected.
+    DuplicateKey {
+        key: String,
+        line: usize,
+        column: usize,
+    },
+    /// A value refers to an environment variable that is not set.
+    UnsetVariable(String),
+    /// A key is set in both configs being merged while repeated keys are
+    /// rejected.
+    ConflictingKey { section: String, key: String },
+    /// A section does not exist.
+    MissingSection(String),
+    /// A section already exists.
+    SectionExists(String),
+    /// A config cannot be deserialized into the requested type.
+    Deserialize(String),
+    /// A value refers to a key whose value refers back to it, directly or
+    /// through other keys.
+    CyclicReference { section: String, key: String },
+    /// A comment contains a line break, so it cannot be written on one line.
+    MultilineComment(String),
+    /// A file could not be read or parsed, with the path of the file.
+    File {
+        path: std::path::PathBuf,
+        error: Box<Error>,
+    },
+    /// A chain of references from a key is nested more deeply than allowed.
+    InterpolationDepth { section: String, key: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse { line, column } => {
+                write!(f, "parse error at line {line}, column {column}")
+            }
+            Error::UndefinedParent(name) => write!(f, "parent section `{name}` is not declared"),
+            Error::AmbiguousSection(name) => {
+                write!(f, "more than one section matches `{name}` ignoring case")
+            }
+            Error::AmbiguousKey(name) => {
+                write!(f, "more than one key matches `{name}` ignoring case")
+            }
+            Error::MissingKey(name) => write!(f, "key `{name}` does not exist"),
+            Error::MissingKeys(names) => write!(f, "keys `{}` do not exist", names.join("`, `")),
+            Error::Type(name) => write!(f, "key `{name}` has a value of the wrong type"),
+            Error::Tab { line, column } => {
+                write!(f, "tab at line {line}, column {column}; use spaces instead")
+            }
+            Error::EmptyValue(name) => write!(f, "key `{name}` has an empty value"),
+            Error::NameDepth(name) => write!(f, "name `{name}` has too many dotted parts"),
+            Error::UnexpectedEqual { line, column } => write!(
+                f,
+                "unexpected `=` in value at line {line}, column {column}; quote the value"
+            ),
+            Error::ReservedName(name) => write!(f, "key name `{name}` is reserved"),
+            Error::UnknownSection(name) => write!(f, "unknown section `{name}`"),
+            Error::UnknownKey { section, key } => {
+                write!(f, "unknown key `{key}` in section `{section}`")
+            }
+            Error::Multiple(errors) => {
+                let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+            Error::LeadingDot(name) => write!(f, "name `{name}` starts with a `.`"),
+            Error::Io(error) => write!(f, "I/O error: {error}"),
+            Error::DuplicateKey { key, line, column } => {
+                write!(f, "duplicate key `{key}` at line {line}, column {column}")
+            }
+            Error::UnsetVariable(name) => write!(f, "environment variable `{name}` is not set"),
+            Error::ConflictingKey { section, key } => {
+                write!(
+                    f,
+                    "key `{key}` in section `{section}` is set in both configs"
+                )
+            }
+            Error::MissingSection(name) => write!(f, "section `{name}` does not exist"),
+            Error::SectionExists(name) => write!(f, "section `{name}` already exists"),
+            Error::Deserialize(message) => write!(f, "cannot deserialize config: {message}"),
+            Error::CyclicReference { section, key } => {
+                write!(f, "key `{key}` in section `{section}` refers to itself")
+            }
+            Error::MultilineComment(comment) => {
+                write!(
+                    f,
+                    "comment `{}` contains a line break",
+                    comment.escape_debug()
+                )
+            }
+            Error::File { path, error } => write!(f, "{}: {error}", path.display()),
+            Error::InterpolationDepth { section, key } => {
+                write!(
+                    f,
+                    "references through key `{key}` in section `{section}` are nested too deeply"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::File { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    /// Compare errors by variant and contents. I/O errors are compared by
+    /// kind, since `std::io::Error` does not implement `PartialEq`.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Error::Parse {
+                    line: a_line,
+                    column: a_column,
+                },
+                Error::Parse {
+                    line: b_line,
+                    column: b_column,
+                },
+            ) => a_line == b_line && a_column == b_column,
+            (Error::UndefinedParent(a), Error::UndefinedParent(b)) => a == b,
+            (Error::AmbiguousSection(a), Error::AmbiguousSection(b)) => a == b,
+            (Error::AmbiguousKey(a), Error::AmbiguousKey(b)) => a == b,
+            (Error::MissingKey(a), Error::MissingKey(b)) => a == b,
+            (Error::MissingKeys(a), Error::MissingKeys(b)) => a == b,
+            (Error::Type(a), Error::Type(b)) => a == b,
+            (
+                Error::Tab {
+                    line: a_line,
+                    column: a_column,
+                },
+                Error::Tab {
+                    line: b_line,
+                    column: b_column,
+                },
+            ) => a_line == b_line && a_column == b_column,
+            (Error::EmptyValue(a), Error::EmptyValue(b)) => a == b,
+            (Error::NameDepth(a), Error::NameDepth(b)) => a == b,
+            (
+                Error::UnexpectedEqual {
+                    line: a_line,
+                    column: a_column,
+                },
+                Error::UnexpectedEqual {
+                    line: b_line,
+                    column: b_column,
+                },
+            ) => a_line == b_line && a_column == b_column,
+            (Error::ReservedName(a), Error::ReservedName(b)) => a == b,
+            (Error::UnknownSection(a), Error::UnknownSection(b)) => a == b,
+            (
+                Error::UnknownKey {
+                    section: a_section,
+                    key: a_key,
+                },
+                Error::UnknownKey {
+                    section: b_section,
+                    key: b_key,
+                },
+            ) => a_section == b_section && a_key == b_key,
+            (Error::Multiple(a), Error::Multiple(b)) => a == b,
+            (Error::LeadingDot(a), Error::LeadingDot(b)) => a == b,
+            (
+                Error::DuplicateKey {
+                    key: a_key,
+                    line: a_line,
+                    column: a_column,
+                },
+                Error::DuplicateKey {
+                    key: b_key,
+                    line: b_line,
+                    column: b_column,
+                },
+            ) => a_key == b_key && a_line == b_line && a_column == b_column,
+            (Error::UnsetVariable(a), Error::UnsetVariable(b)) => a == b,
+            (
+                Error::ConflictingKey {
+                    section: a_section,
+                    key: a_key,
+                },
+                Error::ConflictingKey {
+                    section: b_section,
+                    key: b_key,
+                },
+            ) => a_section == b_section && a_key == b_key,
+            (Error::MissingSection(a), Error::MissingSection(b)) => a == b,
+            (Error::SectionExists(a), Error::SectionExists(b)) => a == b,
+            (Error::Deserialize(a), Error::Deserialize(b)) => a == b,
+            (
+                Error::CyclicReference {
+                    section: a_section,
+                    key: a_key,
+                },
+                Error::CyclicReference {
+                    section: b_section,
+                    key: b_key,
+                },
+            ) => a_section == b_section && a_key == b_key,
+            (Error::MultilineComment(a), Error::MultilineComment(b)) => a == b,
+            (
+                Error::File {
+                    path: a_path,
+                    error: a_error,
+                },
+                Error::File {
+                    path: b_path,
+                    error: b_error,
+                },
+            ) => a_path == b_path && a_error == b_error,
+            (
+                Error::InterpolationDepth {
+                    section: a_section,
+                    key: a_key,
+                },
+                Error::InterpolationDepth {
+                    section: b_section,
+                    key: b_key,
+                },
+            ) => a_section == b_section && a_key == b_key,
+            (Error::Io(a), Error::Io(b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
 }
 
 /// Result type for INI operations.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use crate::{Error, Ini};
+
+    #[test]
+    fn boxed_error() {
+        fn parse() -> std::result::Result<Ini, Box<dyn std::error::Error>> {
+            Ok(Ini::from_str("[foo\n]")?)
+        }
+        let error = parse().unwrap_err();
+        assert_eq!(error.to_string(), "parse error at line 1, column 5");
+    }
+
+    #[test]
+    fn io_error() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file: a.ini");
+        let error = Error::Io(io);
+        assert_eq!(error.to_string(), "I/O error: no such file: a.ini");
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), "no such file: a.ini");
+        assert_eq!(error, Error::Io(std::io::ErrorKind::NotFound.into()));
+        assert_ne!(error, Error::Io(std::io::ErrorKind::Other.into()));
+        assert!(Error::MissingKey("a".into()).source().is_none());
+    }
+}
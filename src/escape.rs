@@ -0,0 +1,139 @@
+use crate::{
+    error::Result,
+    lexer::{is_bare_char, Lexer, Token},
+    ParseOptions,
+};
+
+/// Escape a value for writing to INI text.
+///
+/// Values made up only of alphanumerics and `_.-` are returned as is.
+/// Any other value, including the empty value, is wrapped in double quotes,
+/// with quotes, backslashes, and the control characters that have an escape
+/// sequence escaped. This is the form the serializer uses for names and
+/// values.
+///
+/// ```
+/// use ini::escape_value;
+///
+/// assert_eq!(escape_value("foo"), "foo");
+/// assert_eq!(escape_value(r#"say "hi""#), r#""say \"hi\"""#);
+/// assert_eq!(escape_value("a\tb"), r#""a\tb""#);
+/// ```
+pub fn escape_value(value: &str) -> String {
+    let bare = !value.is_empty() && value.chars().all(is_bare_char);
+    if bare {
+        value.into()
+    } else {
+        let mut escaped = String::from('"');
+        for c in value.chars() {
+            match c {
+                '"' => escaped.push_str(r#"\""#),
+                '\\' => escaped.push_str(r"\\"),
+                '\n' => escaped.push_str(r"\n"),
+                '\t' => escaped.push_str(r"\t"),
+                '\r' => escaped.push_str(r"\r"),
+                '\0' => escaped.push_str(r"\0"),
+                c => escaped.push(c),
+            }
+        }
+        escaped.push('"');
+        escaped
+    }
+}
+
+/// Read a value as written in INI text.
+///
+/// The text must hold a single bare or quoted string, as it would appear
+/// after the `=` of a key. Quotes are removed and escapes are replaced by the
+/// characters they stand for. Surrounding whitespace and a trailing comment
+/// are ignored, as they are by the parser. Returns `Error::Parse` if the text
+/// is not a single string, for example if a quote is not terminated.
+///
+/// ```
+/// use ini::unescape_value;
+///
+/// assert_eq!(unescape_value("foo"), Ok("foo".into()));
+/// assert_eq!(unescape_value(r#""say \"hi\"""#), Ok(r#"say "hi""#.into()));
+/// assert!(unescape_value(r#""foo"#).is_err());
+/// ```
+pub fn unescape_value(text: &str) -> Result<String> {
+    let mut lexer = Lexer::with_options(text, &ParseOptions::default());
+    let value = match lexer.next()? {
+        Some(Token::String(value)) => value,
+        _ => return Err(lexer.error_at(0)),
+    };
+    let pos = lexer.next_token_pos();
+    match lexer.next()? {
+        Some(Token::Comment(_)) if lexer.next()?.is_none() => Ok(value),
+        None => Ok(value),
+        Some(_) => Err(lexer.error_at(pos)),
+    }
+}
+
+/// Replace the escapes in the contents of a quoted string.
+///
+/// Unknown escapes are kept as written, backslash included.
+pub(crate) fn unescape_quoted(contents: &str) -> String {
+    let mut unescaped = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => unescaped.push('"'),
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('0') => unescaped.push('\0'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for value in ["foo", "3.14", "", "two words", r#"a "quoted" word"#, "a=b"] {
+            assert_eq!(unescape_value(&escape_value(value)), Ok(value.into()));
+        }
+        assert_eq!(unescape_value("foo ; comment"), Ok("foo".into()));
+        for value in ["a\\b", "line1\nline2", "\t\r\0", r#"\""#, "end\\"] {
+            assert_eq!(unescape_value(&escape_value(value)), Ok(value.into()));
+        }
+        assert_eq!(escape_value("café"), "café");
+        assert_eq!(escape_value("東京 駅"), "\"東京 駅\"");
+    }
+
+    #[test]
+    fn unescape_sequences() {
+        assert_eq!(
+            unescape_value(r#""line1\nline2""#),
+            Ok("line1\nline2".into())
+        );
+        assert_eq!(unescape_value(r#""a\tb""#), Ok("a\tb".into()));
+        assert_eq!(unescape_value(r#""a\rb""#), Ok("a\rb".into()));
+        assert_eq!(unescape_value(r#""a\\b""#), Ok("a\\b".into()));
+        assert_eq!(unescape_value(r#""a\0b""#), Ok("a\0b".into()));
+        assert_eq!(unescape_value(r#""a\"b""#), Ok("a\"b".into()));
+        assert_eq!(unescape_value(r#""a\\""#), Ok("a\\".into()));
+        assert_eq!(unescape_value(r#""C:\dir""#), Ok(r"C:\dir".into()));
+    }
+
+    #[test]
+    fn unescape_invalid() {
+        assert!(unescape_value("foo bar").is_err());
+        assert!(unescape_value(r#""foo"#).is_err());
+        assert!(unescape_value("[foo]").is_err());
+    }
+}
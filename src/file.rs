@@ -0,0 +1,141 @@
+use std::{fs, io::Read, path::Path};
+
+use crate::{error::Result, Error, Ini};
+
+impl Ini {
+    /// Read and parse a file.
+    ///
+    /// A leading UTF-8 byte order mark, as saved by some Windows editors, is
+    /// ignored. Fails with `Error::Io` if the file cannot be read.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Ini> {
+        let text = fs::read_to_string(path).map_err(io_error)?;
+        Ini::from_str(&text)
+    }
+
+    /// Read and parse text from a reader.
+    ///
+    /// The whole source is read before parsing. As with `from_file`, a
+    /// leading byte order mark is ignored. Fails with `Error::Io` if reading
+    /// fails, including if the text is not valid UTF-8.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Ini> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(io_error)?;
+        Ini::from_str(&text)
+    }
+
+    /// Serialize the config to a file, replacing its contents.
+    ///
+    /// The file holds the same text as `to_string`. Fails with `Error::Io`
+    /// if the file cannot be written.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_string()).map_err(io_error)
+    }
+
+    /// Read and merge every `.ini` file in a directory.
+    ///
+    /// This reads drop-in directories such as `conf.d`. Files are read in
+    /// lexical order of their file names, compared byte by byte, so
+    /// `10-base.ini` is read before `20-local.ini`. Each file is parsed into
+    /// the same Ini, so sections are merged and keys in later files overwrite
+    /// keys in earlier ones. Subdirectories and files with other extensions
+    /// are ignored. Files are read as with `from_file`.
+    ///
+    /// If the directory or any file cannot be read, or any file fails to
+    /// parse, no config is returned and the first error is reported. An error
+    /// in a file is wrapped in `Error::File` with the path of the file.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Ini> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "ini") {
+                paths.push(path);
+            }
+        }
+        paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+
+        let mut ini = Ini::new();
+        for path in paths {
+            let parsed = fs::read_to_string(&path)
+                .map_err(io_error)
+                .and_then(|text| ini.parse_into(&text));
+            parsed.map_err(|error| Error::File {
+                path,
+                error: Box::new(error),
+            })?;
+        }
+        Ok(ini)
+    }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::Io(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_round_trip() {
+        let path = std::env::temp_dir().join(format!("ini-file-{}.ini", std::process::id()));
+        let ini = Ini::from_str("a=1\n[foo]\nb=\"two words\"").unwrap();
+        ini.write_to_file(&path).unwrap();
+        assert_eq!(Ini::from_file(&path), Ok(ini));
+
+        fs::write(&path, "\u{feff}[foo]\nb=2").unwrap();
+        assert_eq!(Ini::from_file(&path).unwrap()["foo"]["b"], "2");
+        fs::remove_file(&path).unwrap();
+
+        let missing = Ini::from_file(&path);
+        assert_eq!(missing, Err(Error::Io(std::io::ErrorKind::NotFound.into())));
+    }
+
+    #[test]
+    fn from_reader() {
+        let reader = std::io::Cursor::new(b"\xef\xbb\xbfa=1\n[foo]\nb=2");
+        let ini = Ini::from_reader(reader).unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini["foo"]["b"], "2");
+
+        let invalid = Ini::from_reader(&b"a=\xff"[..]);
+        assert_eq!(
+            invalid,
+            Err(Error::Io(std::io::ErrorKind::InvalidData.into()))
+        );
+    }
+
+    #[test]
+    fn from_dir() {
+        let dir = std::env::temp_dir().join(format!("ini-from-dir-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested.ini")).unwrap();
+        fs::write(dir.join("20-local.ini"), "[db]\nport=6543\n").unwrap();
+        fs::write(
+            dir.join("10-base.ini"),
+            "a=1\n[db]\nhost=localhost\nport=5432",
+        )
+        .unwrap();
+        fs::write(dir.join("30-notes.txt"), "not ini").unwrap();
+
+        let ini = Ini::from_dir(&dir).unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini["db"]["host"], "localhost");
+        assert_eq!(ini["db"]["port"], "6543");
+
+        fs::write(dir.join("40-broken.ini"), "[db").unwrap();
+        let error = Ini::from_dir(&dir).unwrap_err();
+        let path = dir.join("40-broken.ini");
+        assert_eq!(
+            error.to_string(),
+            format!("{}: parse error at line 1, column 4", path.display())
+        );
+        let error = Error::File {
+            path,
+            error: Box::new(Error::Parse { line: 1, column: 4 }),
+        };
+        assert_eq!(Ini::from_dir(&dir), Err(error));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let missing = Ini::from_dir(&dir);
+        assert_eq!(missing, Err(Error::Io(std::io::ErrorKind::NotFound.into())));
+    }
+}
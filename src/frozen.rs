@@ -0,0 +1,77 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::Ini;
+
+/// A read-only config that can be shared between threads.
+///
+/// Created with [`Ini::freeze`], typically once at startup. The returned
+/// `Arc` is `Send + Sync`, and cloning it only increments a reference count,
+/// so each thread or task can hold its own handle without locking. All of the
+/// read methods of [`Ini`] are available through `Deref`, but none of the
+/// mutating ones. To edit the config again, [`FrozenIni::thaw`] it.
+///
+/// ```
+/// use std::thread;
+/// use ini::Ini;
+///
+/// let config = Ini::from_str("[server]\nport=8080").unwrap().freeze();
+/// let handle = {
+///     let config = config.clone();
+///     thread::spawn(move || config.get("server", "port").map(str::to_string))
+/// };
+/// assert_eq!(handle.join().unwrap().as_deref(), Some("8080"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenIni(Ini);
+
+impl Ini {
+    /// Make the config read-only so that it can be shared between threads.
+    pub fn freeze(self) -> Arc<FrozenIni> {
+        Arc::new(FrozenIni(self))
+    }
+}
+
+impl FrozenIni {
+    /// Get the value of a key in a section.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.0.section(section)?.get_str(key)
+    }
+
+    /// Convert back into a mutable `Ini`.
+    ///
+    /// The config is moved out if this is the last handle to it, and cloned
+    /// otherwise, so other handles are unaffected by later edits.
+    pub fn thaw(self: Arc<Self>) -> Ini {
+        Arc::unwrap_or_clone(self).0
+    }
+}
+
+impl Deref for FrozenIni {
+    type Target = Ini;
+
+    fn deref(&self) -> &Ini {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freeze_and_thaw() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let frozen = Ini::from_str("[foo]\na=1").unwrap().freeze();
+        assert_send_sync(&frozen);
+        assert_eq!(frozen.get("foo", "a"), Some("1"));
+        assert_eq!(frozen.get("foo", "b"), None);
+        assert_eq!(frozen["foo"]["a"], "1");
+
+        let shared = frozen.clone();
+        let mut ini = frozen.thaw();
+        ini["foo"].insert("a".into(), "2".into());
+        assert_eq!(shared.get("foo", "a"), Some("1"));
+        assert_eq!(shared.thaw()["foo"]["a"], "1");
+    }
+}
@@ -0,0 +1,82 @@
+use crate::Ini;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Ini {
+    /// Compute a hash of the sections, keys, and values in the config.
+    ///
+    /// The hash does not depend on the order in which sections and keys were
+    /// declared, or on how the source text was formatted, so configs that
+    /// compare equal hash identically. Comments are not included.
+    ///
+    /// The hash is 64-bit FNV-1a over the sections sorted by name, each
+    /// followed by its keys and values sorted by key. Every name and value is
+    /// prefixed with its length in bytes as a little-endian `u64`, so content
+    /// cannot shift between fields. The algorithm is part of the API and only
+    /// changes in a major version, so hashes can be stored across runs. It is
+    /// not a cryptographic hash.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let a = Ini::from_str("[foo]\na=1\nb=2").unwrap();
+    /// let b = Ini::from_str("[foo]\n  b = 2\n  a = 1").unwrap();
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+        for (section, keys) in self.to_btree_map() {
+            hasher.write_str(&section);
+            hasher.write(&(keys.len() as u64).to_le_bytes());
+            for (key, value) in keys {
+                hasher.write_str(&key);
+                hasher.write_str(&value);
+            }
+        }
+        hasher.0
+    }
+}
+
+/// 64-bit FNV-1a hash state.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_str(&mut self, text: &str) {
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_hash() {
+        let ini = Ini::from_str("a=1\n[foo]\nb=2\nc=3").unwrap();
+        let mut reordered = Ini::from_str("[foo]\nc=3\nb=2").unwrap();
+        reordered[""].insert("a".into(), "1".into());
+        assert_eq!(ini.content_hash(), reordered.content_hash());
+
+        let changed = Ini::from_str("a=1\n[foo]\nb=2\nc=4").unwrap();
+        assert_ne!(ini.content_hash(), changed.content_hash());
+        let mut shifted = Ini::from_str("a=1\n[foo]\nb=\"2c\"").unwrap();
+        shifted["foo"].insert("".into(), "3".into());
+        assert_ne!(ini.content_hash(), shifted.content_hash());
+        let empty_section = Ini::from_str("a=1\n[foo]\nb=2\nc=3\n[bar]").unwrap();
+        assert_ne!(ini.content_hash(), empty_section.content_hash());
+    }
+
+    #[test]
+    fn content_hash_stable() {
+        assert_eq!(Ini::new().content_hash(), 0x8820_1fb9_60ff_6465);
+    }
+}
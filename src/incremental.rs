@@ -0,0 +1,284 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::{
+    error::Result,
+    lexer::{Lexer, Token},
+    Ini, ParseOptions,
+};
+
+impl Ini {
+    /// Update the config after an edit, reparsing only the affected sections.
+    ///
+    /// `full_text` is the complete text after the edit, and `changed` is the
+    /// byte range of `full_text` that was inserted or modified. This Ini must
+    /// hold the result of parsing the text as it was before the edit with the
+    /// default options. Use `reparse_range_with` for a config parsed with
+    /// other options.
+    ///
+    /// The text is scanned for section header lines, which is much cheaper
+    /// than parsing it. Only the span from the header before the edit to the
+    /// header after it is parsed, and the sections declared there replace
+    /// their previous contents. Sections whose headers were removed by the
+    /// edit are removed.
+    ///
+    /// The whole text is silently parsed instead when:
+    ///
+    /// - a section in the span is also declared elsewhere in the text,
+    /// - the section after the span is declared more than once,
+    /// - the span fails to parse, for example on a repeated key that the
+    ///   options reject, so errors report positions in `full_text`, or
+    /// - sections inherit from other sections.
+    ///
+    /// Header lines are recognized as lines whose first non-blank character
+    /// is `[`. Quoted values that span lines and contain such a line are not
+    /// supported.
+    pub fn reparse_range(&mut self, full_text: &str, changed: Range<usize>) -> Result<()> {
+        self.reparse_range_with(full_text, changed, &ParseOptions::default())
+    }
+
+    /// Update the config after an edit using the specified options.
+    ///
+    /// `options` must be the options the config was parsed with, since the
+    /// span around the edit is parsed with them. See `reparse_range`.
+    pub fn reparse_range_with(
+        &mut self,
+        full_text: &str,
+        changed: Range<usize>,
+        options: &ParseOptions,
+    ) -> Result<()> {
+        if self.reparse_block(full_text, changed, options).is_none() {
+            *self = Ini::from_str_with(full_text, options)?;
+        }
+        Ok(())
+    }
+
+    /// Reparse the block around a change, returning `None` without changing
+    /// the config if the whole text must be parsed instead.
+    ///
+    /// If the edit leaves the sections declared in the same order, the
+    /// sections in the block are replaced in place. Otherwise the config is
+    /// rebuilt from the block and the sections outside it.
+    fn reparse_block(
+        &mut self,
+        text: &str,
+        changed: Range<usize>,
+        options: &ParseOptions,
+    ) -> Option<()> {
+        if options.section_inheritance {
+            return None;
+        }
+        let fold = |name: &str| match options.case_insensitive {
+            true => name.to_lowercase(),
+            false => name.to_string(),
+        };
+        let headers = header_lines(text, options)?;
+        let block_start = headers
+            .iter()
+            .rev()
+            .map(|(start, _)| *start)
+            .find(|start| *start <= changed.start)
+            .unwrap_or(0);
+        let block_end = headers
+            .iter()
+            .map(|(start, _)| *start)
+            .find(|start| *start > changed.end.max(block_start))
+            .unwrap_or(text.len());
+        let in_block = |start: usize| (block_start..block_end).contains(&start);
+
+        let block_names: Vec<&str> = headers
+            .iter()
+            .filter(|(start, _)| in_block(*start))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        let folded_block: Vec<String> = block_names.iter().map(|name| fold(name)).collect();
+        let mut outside = headers.iter().filter(|(start, _)| !in_block(*start));
+        if outside.any(|(_, name)| folded_block.contains(&fold(name))) {
+            return None;
+        }
+
+        // Comments before the first header belong to its section, so they are
+        // parsed along with the block.
+        let parse_start = comments_start(text, block_start, options);
+        let block = Ini::from_str_with(&text[parse_start..block_end], options).ok()?;
+
+        // Comments at the end of the block belong to the header that follows
+        // it, or to the end of the text.
+        let next = headers
+            .iter()
+            .find(|(start, _)| *start == block_end)
+            .map(|(_, name)| name.as_str());
+        if let Some(next) = next {
+            let declared = headers.iter().filter(|(_, name)| fold(name) == fold(next));
+            if declared.count() > 1 {
+                return None;
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let names = headers
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .filter(|name| seen.insert(fold(name)));
+        let existing = self.sections().map(|(name, _)| name);
+        if existing.filter(|name| !name.is_empty()).eq(names) {
+            let mut updates = Vec::new();
+            if block_start == 0 {
+                updates.push(("", block.section("")?.clone()));
+            }
+            for name in block_names {
+                updates.push((name, block.section(name)?.clone()));
+            }
+            for (name, section) in updates {
+                *self.section_mut(name)? = section;
+            }
+        } else {
+            let mut ini = Ini::with_fold_case(options.case_insensitive);
+            let global = if block_start == 0 { &block } else { &*self };
+            ini.replace_section("", global.section("")?.clone());
+            for (start, name) in &headers {
+                let source = if in_block(*start) { &block } else { &*self };
+                ini.replace_section(name, source.section(name)?.clone());
+            }
+            ini.comments = self.comments.clone();
+            *self = ini;
+        }
+        match next {
+            Some(next) => self.section_mut(next)?.header_comments = block.comments,
+            None => self.comments = block.comments,
+        }
+        Some(())
+    }
+}
+
+/// Find the section header lines in a text, returning the byte offset where
+/// each line starts and the section name it declares. Returns `None` if a
+/// header line is malformed.
+fn header_lines(text: &str, options: &ParseOptions) -> Option<Vec<(usize, String)>> {
+    let mut headers = Vec::new();
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start_matches([' ', '\t']).starts_with('[') {
+            headers.push((start, header_name(line, options)?));
+        }
+        start += line.len();
+    }
+    Some(headers)
+}
+
+/// Find where the run of comment and blank lines that ends at `end` starts.
+fn comments_start(text: &str, end: usize, options: &ParseOptions) -> usize {
+    let mut start = end;
+    for line in text[..end].split_inclusive('\n').rev() {
+        let content = line.trim_start_matches([' ', '\t', '\r', '\n']);
+        if !(content.is_empty() || content.starts_with(options.comment_prefixes.as_slice())) {
+            break;
+        }
+        start -= line.len();
+    }
+    start
+}
+
+/// Read the section name from a header line.
+fn header_name(line: &str, options: &ParseOptions) -> Option<String> {
+    let mut lexer = Lexer::with_options(line, options);
+    match (lexer.next().ok()?, lexer.next().ok()?, lexer.next().ok()?) {
+        (Some(Token::LeftBracket), Some(Token::String(name)), Some(Token::RightBracket)) => {
+            Some(name)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply an edit to the text and check that an incremental reparse gives
+    /// the same result as a full parse.
+    fn check(before: &str, range: Range<usize>, insert: &str) {
+        check_with(before, range, insert, &ParseOptions::new());
+    }
+
+    fn check_with(before: &str, range: Range<usize>, insert: &str, options: &ParseOptions) {
+        let mut ini = Ini::from_str_with(before, options).unwrap();
+        let mut after = before.to_string();
+        after.replace_range(range.clone(), insert);
+        let changed = range.start..range.start + insert.len();
+        ini.reparse_range_with(&after, changed, options).unwrap();
+        let expected = Ini::from_str_with(&after, options).unwrap();
+        assert_eq!(ini, expected);
+        assert_eq!(ini.to_string(), expected.to_string());
+    }
+
+    const TEXT: &str = "a=1\n[foo]\nb=2\nc=3\n[bar]\nd=4\n";
+
+    #[test]
+    fn edit_value() {
+        check(TEXT, 12..13, "20");
+    }
+
+    #[test]
+    fn edit_global() {
+        check(TEXT, 2..3, "10");
+    }
+
+    #[test]
+    fn add_section() {
+        check(TEXT, 18..18, "[baz]\ne=5\n");
+    }
+
+    #[test]
+    fn remove_section() {
+        check(TEXT, 4..18, "");
+    }
+
+    #[test]
+    fn rename_section() {
+        check(TEXT, 5..8, "qux");
+    }
+
+    #[test]
+    fn edit_comments() {
+        let text = "a=1\n[foo]\nb=2\n; about bar\n[bar]\nd=4\n; end\n";
+        check(text, 14..26, "; changed\n");
+        check(text, 32..36, "e=5 ; new\n; more\n");
+        check("a=1\n  ; x\n\n  # y\n[foo]\nb=2\n", 20..21, "c");
+    }
+
+    #[test]
+    fn repeated_section_falls_back() {
+        check("[foo]\na=1\n[bar]\n[foo]\nb=2\n", 8..9, "3");
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut ini = Ini::from_str(TEXT).unwrap();
+        let after = TEXT.replace("b=2", "b=2=3");
+        assert!(ini.reparse_range(&after, 10..15).is_err());
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = ParseOptions::new()
+            .case_insensitive(true)
+            .colon_delimiter(true)
+            .comment_prefixes(&['#']);
+        let text = "a: 1\n[Foo]\nb: 2\n# about bar\n[bar]\nd: 4\n";
+        check_with(text, 14..15, "20", &options);
+        check_with(text, 11..15, "B: 3", &options);
+        check_with(text, 16..27, "# changed", &options);
+        check_with(text, 28..33, "[FOO]", &options);
+
+        let mut ini = Ini::from_str_with(text, &options).unwrap();
+        let after = text.replace("b: 2", "c: 3");
+        ini.reparse_range_with(&after, 11..15, &options).unwrap();
+        assert_eq!(ini["FOO"]["C"], "3");
+        assert_eq!(ini["foo"].get("b"), None);
+    }
+
+    #[test]
+    fn section_inheritance() {
+        let options = ParseOptions::new().section_inheritance(true);
+        check_with("[base]\na=1\n[child : base]\nb=2\n", 9..10, "3", &options);
+    }
+}
@@ -1,17 +1,31 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    iter::Map,
     ops::{Index, IndexMut},
+    slice,
+    sync::Arc,
+    vec,
 };
 
-use crate::parser::Parser;
+use crate::{diagnostic::Diagnostic, map::OrderedMap, parser::Parser, ParseOptions};
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// INI section.
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Section {
-    /// Config keys, indexed by name.
-    keys: HashMap<String, String>,
+    /// Config keys, indexed by name, in insertion order.
+    keys: OrderedMap<String>,
+    /// Inline comments with their prefix, indexed by key name.
+    comments: HashMap<String, String>,
+    /// Comments on the lines before a key, indexed by key name.
+    leading_comments: HashMap<String, Vec<String>>,
+    /// Comments on the lines before the section header, with their prefix.
+    pub(crate) header_comments: Vec<String>,
+    /// Inline comment after the section header, with its prefix.
+    pub(crate) header_comment: Option<String>,
+    /// Earlier values of keys that hold more than one value, in order.
+    earlier: HashMap<String, Vec<String>>,
 }
 
 impl Section {
@@ -20,12 +34,317 @@ impl Section {
         Self::default()
     }
 
+    /// Create a new Section whose key names ignore case.
+    ///
+    /// Keys that differ only in case are the same key, so `section["Name"]`
+    /// and `section["name"]` return the same value. Each key keeps the case
+    /// it was first inserted with, which is used when it is serialized.
+    pub fn new_case_insensitive() -> Self {
+        Self::with_fold_case(true)
+    }
+
+    pub(crate) fn with_fold_case(fold_case: bool) -> Self {
+        Self {
+            keys: OrderedMap::with_fold_case(fold_case),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the name of a key as stored, if the key exists, so that
+    /// comments and earlier values are found whatever the case of `name`.
+    pub(crate) fn stored_name(&self, name: &str) -> String {
+        self.keys
+            .key(name)
+            .map_or(name, |stored| stored)
+            .to_string()
+    }
+
     /// Insert a key.
     ///
     /// If a key exists with the same name, it is overwritten.
     pub fn insert(&mut self, name: String, value: String) {
+        self.insert_shared(name.into(), value);
+    }
+
+    /// Insert a key with a shared name.
+    pub(crate) fn insert_shared(&mut self, name: Arc<str>, value: String) {
+        self.earlier.remove(&self.stored_name(&name));
         self.keys.insert(name, value);
     }
+
+    /// Add a value to a key with a shared name, keeping any existing values.
+    pub(crate) fn append_shared(&mut self, name: Arc<str>, value: String) {
+        let stored = self.stored_name(&name);
+        if let Some(previous) = self.keys.insert(name, value) {
+            self.earlier.entry(stored).or_default().push(previous);
+        }
+    }
+
+    /// Iterate over every value in the section, including earlier values of
+    /// multi-valued keys.
+    pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let earlier = self.earlier.values_mut().flatten();
+        earlier.chain(self.keys.values_mut())
+    }
+
+    /// Get the values of a key before its current value.
+    pub(crate) fn earlier(&self, name: &str) -> &[String] {
+        let earlier = self.earlier.get(&self.stored_name(name));
+        earlier.map_or(&[], Vec::as_slice)
+    }
+
+    /// Iterate over the shared key names.
+    #[cfg(test)]
+    pub(crate) fn shared_names(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.keys.iter().map(|(name, _)| name)
+    }
+
+    /// Get the value of a key.
+    ///
+    /// Returns `None` if there is no key with the specified name.
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.keys.get(name)
+    }
+
+    /// Get every value of a key, in the order they were added.
+    ///
+    /// A key holds several values when it is declared with the list suffix,
+    /// as in `path[]=lib`, or when it is repeated in a file parsed with
+    /// `DuplicateKeyPolicy::Collect`. Otherwise this holds at most one value.
+    /// Returns an empty list if there is no key with the specified name.
+    ///
+    /// ```
+    /// use ini::{DuplicateKeyPolicy, Ini, ParseOptions};
+    ///
+    /// let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Collect);
+    /// let ini = Ini::from_str_with("path=lib\npath=bin", &options).unwrap();
+    /// assert_eq!(ini[""].get_all("path"), ["lib", "bin"]);
+    /// assert_eq!(ini[""]["path"], "bin");
+    /// ```
+    pub fn get_all(&self, name: &str) -> Vec<&String> {
+        self.earlier(name).iter().chain(self.get(name)).collect()
+    }
+
+    /// Get a mutable reference to the value of a key.
+    ///
+    /// Returns `None` if there is no key with the specified name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut String> {
+        self.keys.get_mut(name)
+    }
+
+    /// Get the entry for a key, for in-place updates.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let mut ini = Ini::new();
+    /// for word in ["a", "b", "a"] {
+    ///     ini[""]
+    ///         .entry(word.into())
+    ///         .and_modify(|count| *count = (count.parse::<u32>().unwrap() + 1).to_string())
+    ///         .or_insert("1".into());
+    /// }
+    ///
+    /// assert_eq!(ini[""]["a"], "2");
+    /// assert_eq!(ini[""]["b"], "1");
+    /// ```
+    pub fn entry(&mut self, name: String) -> Entry<'_> {
+        if self.keys.contains_key(&name) {
+            let value = self.keys.get_mut(&name).expect("key should exist");
+            Entry::Occupied(OccupiedEntry { value })
+        } else {
+            Entry::Vacant(VacantEntry {
+                section: self,
+                name,
+            })
+        }
+    }
+
+    /// Get a mutable reference to the value of a key, inserting the result
+    /// of a function if the key does not exist.
+    ///
+    /// The function is only called if the key does not exist.
+    pub fn get_or_insert_with<F: FnOnce() -> String>(&mut self, name: &str, f: F) -> &mut String {
+        self.entry(name.into()).or_insert_with(f)
+    }
+
+    /// Remove a key, returning its value if it existed.
+    ///
+    /// The key's comments are removed as well.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let stored = self.stored_name(name);
+        self.comments.remove(&stored);
+        self.leading_comments.remove(&stored);
+        self.earlier.remove(&stored);
+        self.keys.remove(name)
+    }
+
+    /// Remove all keys, along with their comments.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.comments.clear();
+        self.leading_comments.clear();
+        self.earlier.clear();
+    }
+
+    /// Get the value of a key as a string slice.
+    pub(crate) fn get_str(&self, name: &str) -> Option<&str> {
+        self.keys.get(name).map(String::as_str)
+    }
+
+    /// Get the value of a key, or a default value if the key does not exist.
+    pub fn get_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.get_str(name).unwrap_or(default)
+    }
+
+    /// Get the value of a key, or compute a value if the key does not exist.
+    ///
+    /// The function is only called if the key does not exist.
+    pub fn get_or_else<F: FnOnce() -> String>(&self, name: &str, f: F) -> String {
+        match self.get_str(name) {
+            Some(value) => value.into(),
+            None => f(),
+        }
+    }
+
+    /// Check whether a key exists.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.keys.contains_key(name)
+    }
+
+    /// Get the value of a key, ignoring case.
+    ///
+    /// Returns `None` if no key matches. If more than one key matches, for
+    /// example both `Port` and `port`, returns `Error::AmbiguousKey`.
+    pub fn get_ignore_case(&self, name: &str) -> Result<Option<&String>> {
+        let mut matches = self
+            .keys
+            .iter()
+            .filter(|(key, _)| eq_ignore_case(key, name));
+        match (matches.next(), matches.next()) {
+            (Some(_), Some(_)) => Err(Error::AmbiguousKey(name.into())),
+            (found, _) => Ok(found.map(|(_, value)| value)),
+        }
+    }
+
+    /// Set the inline comment for a key.
+    ///
+    /// The comment is emitted after the key's value when the config is
+    /// serialized. Any existing comment for the key is replaced, keeping its
+    /// comment character; a new comment starts with `;`. Returns
+    /// `Error::MissingKey` if there is no key with the specified name, and
+    /// `Error::MultilineComment` if the comment contains a line break.
+    pub fn set_comment(&mut self, key: &str, comment: &str) -> Result<()> {
+        if !self.keys.contains_key(key) {
+            return Err(Error::MissingKey(key.into()));
+        }
+        check_comment(comment)?;
+        let stored = self.stored_name(key);
+        let prefix = self
+            .comments
+            .get(&stored)
+            .and_then(|raw| raw.chars().next());
+        let raw = format!("{} {comment}", prefix.unwrap_or(';'));
+        self.comments.insert(stored, raw);
+        Ok(())
+    }
+
+    /// Set the inline comment after the section header.
+    ///
+    /// Any existing comment is replaced, keeping its comment character; a
+    /// new comment starts with `;`. Returns `Error::MultilineComment` if the
+    /// comment contains a line break.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let mut ini = Ini::from_str("[mail]\nport=143").unwrap();
+    /// ini["mail"].set_header_comment("incoming").unwrap();
+    /// assert_eq!(ini.to_string(), "[mail] ; incoming\nport=143\n");
+    /// ```
+    pub fn set_header_comment(&mut self, comment: &str) -> Result<()> {
+        check_comment(comment)?;
+        let prefix = self
+            .header_comment
+            .as_ref()
+            .and_then(|raw| raw.chars().next());
+        self.header_comment = Some(format!("{} {comment}", prefix.unwrap_or(';')));
+        Ok(())
+    }
+
+    /// Get the inline comment for a key with its prefix, if one is set.
+    pub(crate) fn inline_comment(&self, key: &str) -> Option<&str> {
+        self.comments
+            .get(&self.stored_name(key))
+            .map(String::as_str)
+    }
+
+    /// Set the inline comment for a key with its prefix, as read by the
+    /// parser.
+    pub(crate) fn set_inline_comment(&mut self, key: &str, raw: String) {
+        self.comments.insert(self.stored_name(key), raw);
+    }
+
+    /// Get the inline comment for a key, if one is set.
+    ///
+    /// This is the comment that followed the key on the same line when it was
+    /// parsed, without the comment character and surrounding whitespace, or
+    /// the comment set with `set_comment`.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("[mail]\nport=143 ; default").unwrap();
+    /// assert_eq!(ini["mail"].comment_for("port"), Some("default"));
+    /// ```
+    pub fn comment_for(&self, key: &str) -> Option<&str> {
+        self.inline_comment(key).map(comment_text)
+    }
+
+    /// Attach comments from the lines before a key to that key.
+    pub(crate) fn add_leading_comments(&mut self, key: &str, comments: &mut Vec<String>) {
+        if !comments.is_empty() {
+            let stored = self.stored_name(key);
+            self.leading_comments
+                .entry(stored)
+                .or_default()
+                .append(comments);
+        }
+    }
+
+    /// Get the comments from the lines before a key, with their prefix.
+    pub(crate) fn leading_comments(&self, key: &str) -> &[String] {
+        self.leading_comments
+            .get(&self.stored_name(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Iterate over the keys in the section as name-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.keys
+            .iter()
+            .map(|(name, value)| (&**name, value.as_str()))
+    }
+
+    /// Returns the number of keys in the section.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check whether the section has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.len() == 0
+    }
+
+    /// Iterate over the names of the keys in the section.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(name, _)| name)
+    }
+
+    /// Iterate over the values of the keys in the section.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(_, value)| value)
+    }
 }
 
 impl Index<&str> for Section {
@@ -33,55 +352,528 @@ impl Index<&str> for Section {
 
     /// Returns a reference to the key with the specified name.
     ///
-    /// Panics if there is no key with the specified name.
+    /// Panics if there is no key with the specified name. Prefer
+    /// `Section::get` when the key may be absent.
     fn index(&self, name: &str) -> &Self::Output {
-        &self.keys[name]
+        let exp = format!("key {name} should exist");
+        self.keys.get(name).expect(&exp)
     }
 }
 
 impl IndexMut<&str> for Section {
     /// Returns a mutable reference to the key with the specified name.
     ///
-    /// Panics if there is no key with the specified name.
+    /// Panics if there is no key with the specified name. Prefer
+    /// `Section::get_mut` when the key may be absent.
     fn index_mut(&mut self, name: &str) -> &mut Self::Output {
         let exp = format!("key {name} should exist");
         self.keys.get_mut(name).expect(&exp)
     }
 }
 
+impl IntoIterator for Section {
+    type Item = (String, String);
+    type IntoIter = Map<vec::IntoIter<(Arc<str>, String)>, fn((Arc<str>, String)) -> Self::Item>;
+
+    /// Iterate over the keys in the section as name-value pairs, in
+    /// insertion order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys
+            .into_entries()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+    }
+}
+
+impl<'a> IntoIterator for &'a Section {
+    type Item = (&'a str, &'a str);
+    type IntoIter =
+        Map<slice::Iter<'a, (Arc<str>, String)>, fn(&'a (Arc<str>, String)) -> Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys
+            .entries()
+            .iter()
+            .map(|(name, value)| (name, value))
+    }
+}
+
+/// A key in a section that may or may not exist, created by
+/// `Section::entry`.
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// The key exists.
+    Occupied(OccupiedEntry<'a>),
+    /// The key does not exist.
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Insert the value if the key does not exist, and return a mutable
+    /// reference to the value of the key.
+    pub fn or_insert(self, value: String) -> &'a mut String {
+        self.or_insert_with(|| value)
+    }
+
+    /// Insert the result of a function if the key does not exist, and return
+    /// a mutable reference to the value of the key.
+    ///
+    /// The function is only called if the key does not exist.
+    pub fn or_insert_with<F: FnOnce() -> String>(self, f: F) -> &'a mut String {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Modify the value if the key exists.
+    pub fn and_modify<F: FnOnce(&mut String)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A key that exists in a section.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    value: &'a mut String,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Get the value of the key.
+    pub fn get(&self) -> &String {
+        self.value
+    }
+
+    /// Get a mutable reference to the value of the key.
+    pub fn get_mut(&mut self) -> &mut String {
+        self.value
+    }
+
+    /// Convert the entry into a mutable reference to the value of the key.
+    pub fn into_mut(self) -> &'a mut String {
+        self.value
+    }
+
+    /// Set the value of the key, returning the previous value.
+    pub fn insert(&mut self, value: String) -> String {
+        std::mem::replace(self.value, value)
+    }
+}
+
+/// A key that does not exist in a section.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    section: &'a mut Section,
+    name: String,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Get the name of the key.
+    pub fn key(&self) -> &str {
+        &self.name
+    }
+
+    /// Insert the key, returning a mutable reference to its value.
+    pub fn insert(self, value: String) -> &'a mut String {
+        let name: Arc<str> = self.name.into();
+        self.section.insert_shared(name.clone(), value);
+        self.section.keys.get_mut(&name).expect("key should exist")
+    }
+}
+
 /// INI config.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Ini {
-    /// Config sections, indexed by name.
-    sections: HashMap<String, Section>,
+    /// Config sections, indexed by name, in insertion order.
+    sections: OrderedMap<Section>,
+    /// Comments after the last key, with their prefix.
+    pub(crate) comments: Vec<String>,
 }
 
 impl Ini {
     // Create an Ini with a default section.
     pub fn new() -> Ini {
-        let mut sections = HashMap::new();
-        sections.insert("".into(), Section::new());
-        Ini { sections }
+        Self::with_fold_case(false)
+    }
+
+    /// Create an Ini whose section and key names ignore case.
+    ///
+    /// Sections that differ only in case are the same section, and sections
+    /// added to the config treat key names the same way, so
+    /// `ini["Owner"]["Name"]` and `ini["owner"]["name"]` return the same
+    /// value. Names keep the case they were first inserted with, which is
+    /// used when the config is serialized.
+    pub fn new_case_insensitive() -> Ini {
+        Self::with_fold_case(true)
+    }
+
+    pub(crate) fn with_fold_case(fold_case: bool) -> Ini {
+        let mut sections = OrderedMap::with_fold_case(fold_case);
+        sections.insert("", Section::with_fold_case(fold_case));
+        Ini {
+            sections,
+            comments: Vec::new(),
+        }
+    }
+
+    /// Create an empty section that treats case like this config.
+    pub(crate) fn empty_section(&self) -> Section {
+        Section::with_fold_case(self.sections.fold_case())
+    }
+
+    /// Create an empty config that treats case like this config.
+    pub(crate) fn empty_config(&self) -> Ini {
+        Ini::with_fold_case(self.sections.fold_case())
     }
 
     /// Parse an Ini from an input string.
+    ///
+    /// Fails if the text is not valid INI. A leading UTF-8 byte order mark is
+    /// ignored.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(text: &str) -> Result<Ini> {
         Parser::from_str(text)
     }
 
+    /// Parse an Ini from an input string using the specified options.
+    pub fn from_str_with(text: &str, options: &ParseOptions) -> Result<Ini> {
+        Parser::from_str_with(text, options)
+    }
+
+    /// Parse an Ini from an input string, also returning non-fatal issues.
+    ///
+    /// Diagnostics are returned in the order they appear in the text. They
+    /// are only returned if parsing succeeds.
+    pub fn from_str_with_diagnostics(
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<(Ini, Vec<Diagnostic>)> {
+        Parser::from_str_with_diagnostics(text, options)
+    }
+
+    /// Parse an Ini from an input string, skipping malformed lines.
+    ///
+    /// Instead of stopping at the first error, the parser records it, skips
+    /// to the start of the next line, and continues. Returns every key that
+    /// parsed along with the errors in the order they were found, which is
+    /// useful for reporting all problems in a file at once.
+    ///
+    /// ```
+    /// use ini::{Error, Ini, ParseOptions};
+    ///
+    /// let text = "[foo\na=1\nb=\"2\n[bar]\nc=3";
+    /// let (ini, errors) = Ini::from_str_lenient(text, &ParseOptions::new());
+    /// assert_eq!(ini[""]["a"], "1");
+    /// assert_eq!(ini["bar"]["c"], "3");
+    /// assert_eq!(
+    ///     errors,
+    ///     [
+    ///         Error::Parse { line: 1, column: 5 },
+    ///         Error::Parse { line: 3, column: 3 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn from_str_lenient(text: &str, options: &ParseOptions) -> (Ini, Vec<Error>) {
+        Parser::from_str_lenient(text, options)
+    }
+
+    /// Parse additional text into this Ini.
+    ///
+    /// Sections in the text that already exist are extended rather than
+    /// replaced, and keys that already exist take the value from the text, so
+    /// later text wins. This allows layering several files without building
+    /// an intermediate Ini for each. If parsing fails, keys parsed before the
+    /// error remain in this Ini.
+    pub fn parse_into(&mut self, text: &str) -> Result<()> {
+        Parser::parse_into(text, &ParseOptions::default(), self)
+    }
+
     /// Add an empty section.
     ///
     /// If a section with the specified name already exists, the original
-    /// section will be discarded.
+    /// section will be discarded, and the new section takes its position.
     pub fn add_section(&mut self, name: &str) {
-        self.sections.insert(name.into(), Section::new());
+        self.sections.insert(name, self.empty_section());
+    }
+
+    /// Get a mutable section, adding an empty section if it does not exist.
+    ///
+    /// Unlike `add_section`, an existing section is kept with its keys.
+    pub fn ensure_section(&mut self, name: &str) -> &mut Section {
+        if !self.sections.contains_key(name) {
+            self.sections.insert(name, self.empty_section());
+        }
+        self.sections.get_mut(name).expect("section should exist")
+    }
+
+    /// Set the value of a key, adding the section if it does not exist.
+    ///
+    /// If the key exists, its value is overwritten.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.ensure_section(section)
+            .insert(key.into(), value.into());
+    }
+
+    /// Set a section, returning the previous section with that name.
+    ///
+    /// If the section does not exist, it is added after the existing sections.
+    /// Otherwise it keeps its position.
+    pub fn replace_section(&mut self, name: &str, section: Section) -> Option<Section> {
+        self.sections.insert(name, section)
+    }
+
+    /// Get a section.
+    ///
+    /// Returns `None` if the section does not exist.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.get(name)
+    }
+
+    /// Remove a section, returning it if it existed.
+    ///
+    /// The default section cannot be removed. Removing "" returns its keys
+    /// and leaves an empty default section in its place.
+    pub fn remove_section(&mut self, name: &str) -> Option<Section> {
+        if name.is_empty() {
+            return self.replace_section("", self.empty_section());
+        }
+        self.sections.remove(name)
+    }
+
+    /// Rename a section, keeping its keys and its position.
+    ///
+    /// Returns `Error::MissingSection` if `from` does not exist, and
+    /// `Error::SectionExists` if another section is named `to`. Renaming a
+    /// section to its own name does nothing. Since the default section always
+    /// exists, renaming "" moves its keys to a new section at the end and
+    /// leaves the default section empty, and no section can be renamed to "".
+    pub fn rename_section(&mut self, from: &str, to: &str) -> Result<()> {
+        let Some(stored) = self.sections.key(from) else {
+            return Err(Error::MissingSection(from.into()));
+        };
+        if self
+            .sections
+            .key(to)
+            .is_some_and(|existing| existing != stored)
+        {
+            return Err(Error::SectionExists(to.into()));
+        }
+        if from.is_empty() {
+            let section = self
+                .remove_section("")
+                .expect("default section should exist");
+            self.replace_section(to, section);
+        } else {
+            self.sections.rename(from, to);
+        }
+        Ok(())
+    }
+
+    /// Remove all sections and comments, leaving an empty default section.
+    pub fn clear(&mut self) {
+        self.sections.clear();
+        self.sections.insert("", self.empty_section());
+        self.comments.clear();
+    }
+
+    /// Returns the number of sections, including the default section.
+    ///
+    /// The default section always exists, so this is at least 1.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Check whether the config is empty, like a new `Ini`.
+    ///
+    /// This is the case if the default section is the only section and it
+    /// has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 1 && self[""].is_empty()
+    }
+
+    /// Check whether a section exists.
+    ///
+    /// The default section always exists, under the name "".
+    pub fn contains_section(&self, name: &str) -> bool {
+        self.sections.contains_key(name)
+    }
+
+    /// Get a section, ignoring case.
+    ///
+    /// Returns `None` if no section matches. If more than one section matches,
+    /// for example both `Server` and `server`, returns
+    /// `Error::AmbiguousSection`.
+    pub fn section_ignore_case(&self, name: &str) -> Result<Option<&Section>> {
+        let mut matches = self
+            .sections
+            .iter()
+            .filter(|(section, _)| eq_ignore_case(section, name));
+        match (matches.next(), matches.next()) {
+            (Some(_), Some(_)) => Err(Error::AmbiguousSection(name.into())),
+            (found, _) => Ok(found.map(|(_, section)| section)),
+        }
+    }
+
+    /// Iterate over the sections in the config mutably.
+    pub(crate) fn sections_mut(&mut self) -> impl Iterator<Item = &mut Section> {
+        self.sections.values_mut()
+    }
+
+    /// Iterate over the sections in the config, including the default section.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, &Section)> {
+        self.sections
+            .iter()
+            .map(|(name, section)| (&**name, section))
     }
 
     /// Get a mutable section.
     ///
-    /// If the section does not exist, this will panic.
-    pub fn section_mut(&mut self, name: &str) -> &mut Section {
-        self.sections.get_mut(name).unwrap()
+    /// Returns `None` if the section does not exist.
+    pub fn section_mut(&mut self, name: &str) -> Option<&mut Section> {
+        self.sections.get_mut(name)
+    }
+
+    /// Iterate over every key in the config by its qualified name.
+    ///
+    /// Keys are named `section.key`, joined with a `.`, or just `key` for
+    /// keys in the default section. Entries are yielded in declaration order,
+    /// starting with the default section.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("a=1\n[foo]\nb=2").unwrap();
+    /// let names: Vec<_> = ini.qualified_entries().map(|(name, _)| name).collect();
+    /// assert_eq!(names, ["a", "foo.b"]);
+    /// ```
+    pub fn qualified_entries(&self) -> impl Iterator<Item = (String, &String)> {
+        self.sections.iter().flat_map(|(section, keys)| {
+            keys.keys.iter().map(move |(key, value)| {
+                let name = match &**section {
+                    "" => key.to_string(),
+                    section => format!("{section}.{key}"),
+                };
+                (name, value)
+            })
+        })
+    }
+
+    /// Copy the config into sorted maps.
+    ///
+    /// Sections are keyed by name at the first level and keys at the second
+    /// level. The default section is included under the name "".
+    pub fn to_btree_map(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+        self.sections
+            .iter()
+            .map(|(name, section)| {
+                let keys = section
+                    .keys
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect();
+                (name.to_string(), keys)
+            })
+            .collect()
+    }
+
+    /// Copy the config into hash maps.
+    ///
+    /// Sections are keyed by name at the first level and keys at the second
+    /// level. The default section is included under the name "". Comments and
+    /// the order of sections and keys are not kept.
+    pub fn to_map(&self) -> HashMap<String, HashMap<String, String>> {
+        self.sections
+            .iter()
+            .map(|(name, section)| {
+                let keys = section
+                    .keys
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect();
+                (name.to_string(), keys)
+            })
+            .collect()
+    }
+
+    /// Build a config from hash maps, as returned by `to_map`.
+    ///
+    /// Keys under the name "" go to the default section. Since maps have no
+    /// order, sections and keys are added sorted by name.
+    pub fn from_map(map: HashMap<String, HashMap<String, String>>) -> Ini {
+        let mut sections: Vec<_> = map.into_iter().collect();
+        sections.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut ini = Ini::new();
+        for (name, keys) in sections {
+            let mut keys: Vec<_> = keys.into_iter().collect();
+            keys.sort();
+            let mut section = ini.empty_section();
+            for (key, value) in keys {
+                section.insert(key, value);
+            }
+            ini.replace_section(&name, section);
+        }
+        ini
+    }
+}
+
+impl Default for Ini {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<(String, String, String)> for Ini {
+    /// Build a config from section, key, and value triples.
+    ///
+    /// Sections are created as they are first named, and later values of a
+    /// key overwrite earlier ones.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini: Ini = [("db", "port", "5432"), ("", "level", "debug")]
+    ///     .into_iter()
+    ///     .map(|(s, k, v)| (s.into(), k.into(), v.into()))
+    ///     .collect();
+    ///
+    /// assert_eq!(ini.to_string(), "level=debug\n\n[db]\nport=5432\n");
+    /// ```
+    fn from_iter<I: IntoIterator<Item = (String, String, String)>>(iter: I) -> Self {
+        let mut ini = Ini::new();
+        for (section, key, value) in iter {
+            ini.ensure_section(&section).insert(key, value);
+        }
+        ini
+    }
+}
+
+impl IntoIterator for Ini {
+    type Item = (String, Section);
+    type IntoIter = Map<vec::IntoIter<(Arc<str>, Section)>, fn((Arc<str>, Section)) -> Self::Item>;
+
+    /// Iterate over the sections in the config, including the default
+    /// section, in insertion order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.sections
+            .into_entries()
+            .into_iter()
+            .map(|(name, section)| (name.to_string(), section))
+    }
+}
+
+impl<'a> IntoIterator for &'a Ini {
+    type Item = (&'a str, &'a Section);
+    type IntoIter =
+        Map<slice::Iter<'a, (Arc<str>, Section)>, fn(&'a (Arc<str>, Section)) -> Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sections
+            .entries()
+            .iter()
+            .map(|(name, section)| (name, section))
     }
 }
 
@@ -90,18 +882,555 @@ impl Index<&str> for Ini {
 
     /// Returns a reference to the section with the specified name.
     ///
-    /// Panics if there is no section with the specified name.
+    /// Panics if there is no section with the specified name. Prefer
+    /// `Ini::section` when the section may be absent.
     fn index(&self, name: &str) -> &Self::Output {
-        &self.sections[name]
+        let exp = format!("section {name} should exist");
+        self.sections.get(name).expect(&exp)
     }
 }
 
 impl IndexMut<&str> for Ini {
     /// Returns a mutable reference to the section with the specified name.
     ///
-    /// Panics if there is no section with the specified name.
+    /// Panics if there is no section with the specified name. Prefer
+    /// `Ini::section_mut` when the section may be absent.
     fn index_mut(&mut self, name: &str) -> &mut Self::Output {
         let exp = format!("section {name} should exist");
         self.sections.get_mut(name).expect(&exp)
     }
 }
+
+/// Compare two names without regard to case.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Check that a comment can be written on a single line.
+fn check_comment(comment: &str) -> Result<()> {
+    match comment.contains(['\n', '\r']) {
+        true => Err(Error::MultilineComment(comment.into())),
+        false => Ok(()),
+    }
+}
+
+/// Returns the text of a comment without the comment character and
+/// surrounding whitespace.
+pub(crate) fn comment_text(comment: &str) -> &str {
+    let mut text = comment.chars();
+    text.next();
+    text.as_str().trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DuplicateKeyPolicy;
+
+    #[test]
+    fn ensure_section() {
+        let mut ini = Ini::from_str("[x]\na=1").unwrap();
+        ini.ensure_section("x").insert("b".into(), "2".into());
+        ini.ensure_section("y").insert("c".into(), "3".into());
+        assert_eq!(ini.to_string(), "[x]\na=1\nb=2\n\n[y]\nc=3\n");
+
+        ini.add_section("x");
+        assert!(ini["x"].is_empty());
+    }
+
+    #[test]
+    fn set() {
+        let mut ini = Ini::new();
+        ini.set("server", "port", "80");
+        ini.set("", "level", "debug");
+        ini.set("server", "port", "8080");
+        assert_eq!(ini["server"]["port"], "8080");
+        assert_eq!(ini.to_string(), "level=debug\n\n[server]\nport=8080\n");
+    }
+
+    #[test]
+    fn clear() {
+        let mut ini = Ini::from_str("a=1\n[foo]\nb=2 ; note\n; end").unwrap();
+        ini.clear();
+        assert!(ini.is_empty());
+        assert_eq!(ini, Ini::new());
+        assert_eq!(ini.to_string(), "");
+        ini.set("foo", "c", "3");
+        assert_eq!(ini.to_string(), "[foo]\nc=3\n");
+
+        let mut ini = Ini::from_str("[foo]\na=1 ; note\nb=2").unwrap();
+        ini["foo"].clear();
+        assert!(ini["foo"].is_empty());
+        ini["foo"].insert("a".into(), "3".into());
+        assert_eq!(ini.to_string(), "[foo]\na=3\n");
+    }
+
+    #[test]
+    fn rename_section() {
+        let mut ini = Ini::from_str("a=1\n[foo]\nb=2\n[bar]\nc=3").unwrap();
+        ini.rename_section("foo", "baz").unwrap();
+        assert_eq!(ini.to_string(), "a=1\n\n[baz]\nb=2\n\n[bar]\nc=3\n");
+        assert!(!ini.contains_section("foo"));
+
+        ini.rename_section("baz", "baz").unwrap();
+        assert_eq!(ini["baz"]["b"], "2");
+
+        ini.rename_section("", "global").unwrap();
+        assert_eq!(
+            ini.to_string(),
+            "[baz]\nb=2\n\n[bar]\nc=3\n\n[global]\na=1\n"
+        );
+        assert!(ini[""].is_empty());
+    }
+
+    #[test]
+    fn rename_section_errors() {
+        let mut ini = Ini::from_str("[foo]\nb=2\n[bar]\nc=3").unwrap();
+        let original = ini.clone();
+        assert_eq!(
+            ini.rename_section("foo", "bar"),
+            Err(Error::SectionExists("bar".into()))
+        );
+        assert_eq!(
+            ini.rename_section("foo", ""),
+            Err(Error::SectionExists("".into()))
+        );
+        assert_eq!(
+            ini.rename_section("missing", "baz"),
+            Err(Error::MissingSection("missing".into()))
+        );
+        assert_eq!(ini, original);
+    }
+
+    #[test]
+    fn rename_section_case_insensitive() {
+        let mut ini = Ini::new_case_insensitive();
+        ini.add_section("Foo");
+        ini.add_section("Bar");
+        ini.rename_section("foo", "FOO").unwrap();
+        assert_eq!(
+            ini.sections().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["", "FOO", "Bar"]
+        );
+        assert!(ini.contains_section("foo"));
+        assert!(ini.rename_section("FOO", "bar").is_err());
+    }
+
+    #[test]
+    fn to_map() {
+        let ini = Ini::from_str("a=1\n[foo]\nc=3\nb=2\n[bar]").unwrap();
+        let map = ini.to_map();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[""]["a"], "1");
+        assert_eq!(map["foo"]["c"], "3");
+        assert!(map["bar"].is_empty());
+
+        let round_trip = Ini::from_map(map);
+        assert_eq!(round_trip, ini);
+        assert_eq!(round_trip.to_string(), "a=1\n\n[bar]\n\n[foo]\nb=2\nc=3\n");
+        assert_eq!(Ini::from_map(HashMap::new()), Ini::new());
+    }
+
+    #[test]
+    fn to_btree_map() {
+        let ini = Ini::from_str(
+            r"
+            a=1
+            [foo]
+            c=3
+            b=2
+            ",
+        )
+        .unwrap();
+        let map = ini.to_btree_map();
+        let names: Vec<_> = map.keys().map(String::as_str).collect();
+        assert_eq!(names, ["", "foo"]);
+        assert_eq!(map[""]["a"], "1");
+        let keys: Vec<_> = map["foo"].keys().map(String::as_str).collect();
+        assert_eq!(keys, ["b", "c"]);
+    }
+
+    #[test]
+    fn section_ignore_case() {
+        let ini = Ini::from_str("[Server]\nPort=80").unwrap();
+        let section = ini.section_ignore_case("server").unwrap().unwrap();
+        assert_eq!(section.get_ignore_case("PORT"), Ok(Some(&"80".into())));
+        assert_eq!(ini.section_ignore_case("client"), Ok(None));
+    }
+
+    #[test]
+    fn section_ignore_case_ambiguous() {
+        let ini = Ini::from_str("[Server]\n[server]").unwrap();
+        let section = ini.section_ignore_case("SERVER");
+        assert_eq!(section, Err(Error::AmbiguousSection("SERVER".into())));
+    }
+
+    #[test]
+    fn key_ignore_case_ambiguous() {
+        let ini = Ini::from_str("Port=80\nport=81").unwrap();
+        let key = ini[""].get_ignore_case("port");
+        assert_eq!(key, Err(Error::AmbiguousKey("port".into())));
+    }
+
+    #[test]
+    fn comment_for() {
+        let mut ini =
+            Ini::from_str("[mail]\nport=143 ;  default \nhost=a\n# about b\nb=2 #note").unwrap();
+        assert_eq!(ini["mail"].comment_for("port"), Some("default"));
+        assert_eq!(ini["mail"].comment_for("host"), None);
+        assert_eq!(ini["mail"].comment_for("b"), Some("note"));
+        assert_eq!(ini["mail"].comment_for("missing"), None);
+
+        ini["mail"].set_comment("host", "changed").unwrap();
+        assert_eq!(ini["mail"].comment_for("host"), Some("changed"));
+    }
+
+    #[test]
+    fn set_comment_missing_key() {
+        let mut section = Section::new();
+        let result = section.set_comment("port", "default");
+        assert_eq!(result, Err(Error::MissingKey("port".into())));
+    }
+
+    #[test]
+    fn set_comment_line_break() {
+        let mut ini = Ini::from_str("[foo]\nk=v # note").unwrap();
+        let comment = "x\nevil=1";
+        let error = Err(Error::MultilineComment(comment.into()));
+        assert_eq!(ini["foo"].set_comment("k", comment), error);
+        assert_eq!(
+            ini["foo"].set_comment("k", "x\ry"),
+            Err(Error::MultilineComment("x\ry".into()))
+        );
+        assert_eq!(ini["foo"].set_header_comment(comment), error);
+        assert_eq!(ini.to_string(), "[foo]\nk=v # note\n");
+
+        ini["foo"].set_header_comment("header").unwrap();
+        ini["foo"].set_comment("k", "changed").unwrap();
+        assert_eq!(ini.to_string(), "[foo] ; header\nk=v # changed\n");
+    }
+
+    #[test]
+    fn section_get() {
+        let mut ini = Ini::from_str("[foo]\na=1").unwrap();
+        let section = &mut ini["foo"];
+        assert_eq!(section.get("a").map(String::as_str), Some("1"));
+        assert_eq!(section.get("b"), None);
+        *section.get_mut("a").unwrap() = "2".into();
+        assert_eq!(section["a"], "2");
+        assert_eq!(section.get_mut("b"), None);
+    }
+
+    #[test]
+    fn section_mut() {
+        let mut ini = Ini::from_str("[foo]\na=1").unwrap();
+        assert!(ini.section("bar").is_none());
+        assert!(ini.section_mut("bar").is_none());
+        let section = ini.section_mut("foo").unwrap();
+        section.insert("b".into(), "2".into());
+        assert_eq!(ini.section("foo").unwrap()["b"], "2");
+    }
+
+    #[test]
+    fn contains() {
+        let ini = Ini::from_str("a=1\n[foo]\nb=2").unwrap();
+        assert!(ini.contains_section(""));
+        assert!(ini.contains_section("foo"));
+        assert!(!ini.contains_section("bar"));
+        assert!(ini[""].contains_key("a"));
+        assert!(!ini[""].contains_key("b"));
+        assert!(ini["foo"].contains_key("b"));
+        assert!(!Ini::new().contains_section("foo"));
+    }
+
+    #[test]
+    fn remove() {
+        let mut ini = Ini::from_str("a=1\n[foo]\nb=2\nc=3\n[bar]").unwrap();
+        ini["foo"].set_comment("b", "note").unwrap();
+        assert_eq!(ini["foo"].remove("b"), Some("2".into()));
+        assert_eq!(ini["foo"].remove("b"), None);
+        ini["foo"].insert("b".into(), "4".into());
+        assert_eq!(ini["foo"].comment_for("b"), None);
+
+        let foo = ini.remove_section("foo").unwrap();
+        assert_eq!(foo["c"], "3");
+        assert_eq!(ini.remove_section("foo"), None);
+        assert_eq!(ini.to_string(), "a=1\n\n[bar]\n");
+
+        let global = ini.remove_section("").unwrap();
+        assert_eq!(global["a"], "1");
+        assert!(ini.contains_section(""));
+        assert_eq!(ini[""], Section::new());
+    }
+
+    #[test]
+    fn iterate() {
+        let ini = Ini::from_str("b=2\na=1\n[foo]\nc=3").unwrap();
+        let mut entries: Vec<_> = ini
+            .sections()
+            .flat_map(|(name, section)| section.iter().map(move |(key, value)| (name, key, value)))
+            .collect();
+        entries.sort();
+        assert_eq!(entries, [("", "a", "1"), ("", "b", "2"), ("foo", "c", "3")]);
+        assert_eq!(ini[""].keys().collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(ini[""].values().collect::<Vec<_>>(), ["2", "1"]);
+    }
+
+    #[test]
+    fn len() {
+        let ini = Ini::new();
+        assert_eq!(ini.len(), 1);
+        assert!(ini.is_empty());
+
+        let ini = Ini::from_str("a=1").unwrap();
+        assert_eq!(ini.len(), 1);
+        assert!(!ini.is_empty());
+        assert_eq!(ini[""].len(), 1);
+
+        let ini = Ini::from_str("[foo]").unwrap();
+        assert_eq!(ini.len(), 2);
+        assert!(!ini.is_empty());
+        assert!(ini["foo"].is_empty());
+    }
+
+    #[test]
+    fn get_all() {
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Collect);
+        let text = "[foo]\npath=a\nother=1\npath=b\n[bar]\n[foo]\npath=c";
+        let mut ini = Ini::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["foo"].get_all("path"), ["a", "b", "c"]);
+        assert_eq!(ini["foo"]["path"], "c");
+        assert_eq!(ini["foo"].get_all("other"), ["1"]);
+        assert!(ini["foo"].get_all("missing").is_empty());
+
+        ini["foo"].insert("path".into(), "d".into());
+        assert_eq!(ini["foo"].get_all("path"), ["d"]);
+    }
+
+    #[test]
+    fn into_iter() {
+        let ini = Ini::from_str("a=1\n[foo]\nb=2\nc=3\n[bar]").unwrap();
+
+        let mut names = Vec::new();
+        for (name, section) in &ini {
+            names.push(name);
+            assert_eq!(section, &ini[name]);
+        }
+        assert_eq!(names, ["", "foo", "bar"]);
+        let keys: Vec<_> = (&ini["foo"]).into_iter().collect();
+        assert_eq!(keys, [("b", "2"), ("c", "3")]);
+
+        let map: BTreeMap<String, BTreeMap<String, String>> = ini
+            .into_iter()
+            .map(|(name, section)| (name, section.into_iter().collect()))
+            .collect();
+        let expected = BTreeMap::from([
+            ("".into(), BTreeMap::from([("a".into(), "1".into())])),
+            (
+                "foo".into(),
+                BTreeMap::from([("b".into(), "2".into()), ("c".into(), "3".into())]),
+            ),
+            ("bar".into(), BTreeMap::new()),
+        ]);
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(Ini::default(), Ini::new());
+        assert!(Ini::default().contains_section(""));
+
+        let mut ini = Ini::from_str("[foo]").unwrap();
+        let taken = std::mem::take(&mut ini);
+        assert!(ini.is_empty());
+        assert!(taken.contains_section("foo"));
+    }
+
+    #[test]
+    fn clone() {
+        let ini = Ini::from_str("[foo]\na=1 ; note").unwrap();
+        let mut copy = ini.clone();
+        copy["foo"].insert("a".into(), "2".into());
+        copy["foo"].insert("b".into(), "3".into());
+        copy.add_section("bar");
+        assert_eq!(ini.to_string(), "[foo]\na=1 ; note\n");
+        assert_ne!(ini, copy);
+    }
+
+    #[test]
+    fn from_iter() {
+        let triples = vec![
+            ("foo".to_string(), "a".to_string(), "1".to_string()),
+            ("bar".into(), "b".into(), "2".into()),
+            ("foo".into(), "c".into(), "3".into()),
+            ("foo".into(), "a".into(), "4".into()),
+        ];
+        let ini: Ini = triples.into_iter().collect();
+        assert_eq!(ini.len(), 3);
+        assert_eq!(ini.to_string(), "[foo]\na=4\nc=3\n\n[bar]\nb=2\n");
+    }
+
+    #[test]
+    fn entry() {
+        let mut section = Section::new();
+        section.insert("present".into(), "1".into());
+
+        assert_eq!(section.entry("present".into()).or_insert("2".into()), "1");
+        assert_eq!(section.entry("absent".into()).or_insert("2".into()), "2");
+        assert_eq!(section["absent"], "2");
+
+        let value = section.entry("lazy".into()).or_insert_with(|| "3".into());
+        value.push('0');
+        assert_eq!(section["lazy"], "30");
+        section
+            .entry("present".into())
+            .or_insert_with(|| unreachable!());
+
+        section.entry("present".into()).and_modify(|v| v.push('1'));
+        section.entry("new".into()).and_modify(|v| v.push('1'));
+        assert_eq!(section["present"], "11");
+        assert!(!section.contains_key("new"));
+        assert_eq!(section.iter().count(), 3);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut section = Section::new();
+        section.insert("present".into(), "1".into());
+        let mut calls = 0;
+        let mut default = || {
+            calls += 1;
+            "2".to_string()
+        };
+        assert_eq!(section.get_or_insert_with("present", &mut default), "1");
+        assert_eq!(section.get_or_insert_with("absent", &mut default), "2");
+        section.get_or_insert_with("absent", &mut default).push('0');
+        assert_eq!(calls, 1);
+        assert_eq!(section["absent"], "20");
+    }
+
+    #[test]
+    fn entry_variants() {
+        let mut section = Section::new_case_insensitive();
+        section.insert("Key".into(), "a".into());
+        match section.entry("KEY".into()) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.get(), "a");
+                assert_eq!(entry.insert("b".into()), "a");
+            }
+            Entry::Vacant(_) => panic!("key should exist"),
+        }
+        match section.entry("other".into()) {
+            Entry::Vacant(entry) => assert_eq!(entry.key(), "other"),
+            Entry::Occupied(_) => panic!("key should not exist"),
+        }
+        assert_eq!(section.iter().collect::<Vec<_>>(), [("Key", "b")]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let options = ParseOptions::new().case_insensitive(true);
+        let text = "[Owner]\nName=John\n[owner]\nORG=Acme";
+        let mut ini = Ini::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["Owner"]["Name"], "John");
+        assert_eq!(ini["owner"]["name"], "John");
+        assert_eq!(ini.section("OWNER").unwrap().get("org").unwrap(), "Acme");
+
+        ini["owner"].insert("NAME".into(), "Jane".into());
+        ini["owner"].set_comment("name", "changed").unwrap();
+        ini.add_section("Server");
+        ini["server"].insert("Host".into(), "a".into());
+        assert_eq!(ini["SERVER"]["host"], "a");
+        let expected = "[Owner]\nName=Jane ; changed\nORG=Acme\n\n[Server]\nHost=a\n";
+        assert_eq!(ini.to_string(), expected);
+
+        let ini = Ini::from_str(text).unwrap();
+        assert!(ini.section("owner").unwrap().get("name").is_none());
+    }
+
+    #[test]
+    fn from_str_error() {
+        assert_eq!(
+            Ini::from_str("[foo"),
+            Err(Error::Parse { line: 1, column: 5 })
+        );
+        assert_eq!(
+            Ini::from_str("a=\"b"),
+            Err(Error::Parse { line: 1, column: 3 })
+        );
+    }
+
+    #[test]
+    fn from_str_lenient() {
+        let text = "a=1\n[foo]\nb=2 = 3\nc=3\n[bar\nd=4\n[baz]\ne=5";
+        let (ini, errors) = Ini::from_str_lenient(text, &ParseOptions::new());
+        assert_eq!(
+            errors,
+            [
+                Error::UnexpectedEqual { line: 3, column: 5 },
+                Error::Parse { line: 5, column: 5 },
+            ]
+        );
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini["foo"].get("b"), None);
+        assert_eq!(ini["foo"]["c"], "3");
+        assert_eq!(ini.section("bar"), None);
+        assert_eq!(ini["foo"]["d"], "4");
+        assert_eq!(ini["baz"]["e"], "5");
+
+        let (ini, errors) = Ini::from_str_lenient("a=1\nb=2", &ParseOptions::new());
+        assert!(errors.is_empty());
+        assert_eq!(ini, Ini::from_str("a=1\nb=2").unwrap());
+    }
+
+    #[test]
+    fn parse_into() {
+        let mut ini = Ini::from_str("a=1\n[foo]\nb=2\nc=3").unwrap();
+        ini.parse_into("[foo]\nc=4\n[bar]\nd=5").unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini["foo"]["b"], "2");
+        assert_eq!(ini["foo"]["c"], "4");
+        assert_eq!(ini["bar"]["d"], "5");
+    }
+
+    #[test]
+    fn from_str_with_diagnostics() {
+        let text = "[foo]  \nbar=baz ; comment  \n";
+        let (ini, diagnostics) =
+            Ini::from_str_with_diagnostics(text, &ParseOptions::new()).unwrap();
+        assert_eq!(ini["foo"]["bar"], "baz");
+        let expected = Diagnostic {
+            kind: crate::DiagnosticKind::TrailingWhitespace,
+            line: 1,
+            column: 6,
+        };
+        assert_eq!(diagnostics, [expected]);
+    }
+
+    #[test]
+    fn replace_section() {
+        let mut ini = Ini::from_str("[foo]\na=1").unwrap();
+        let mut section = Section::new();
+        section.insert("b".into(), "2".into());
+        let previous = ini.replace_section("foo", section.clone());
+        assert_eq!(previous.unwrap()["a"], "1");
+        assert_eq!(ini["foo"], section);
+        assert_eq!(ini.replace_section("bar", Section::new()), None);
+        assert!(ini.section("bar").is_some());
+    }
+
+    #[test]
+    fn get_or() {
+        let ini = Ini::from_str("a=1\nempty=").unwrap();
+        assert_eq!(ini[""].get_or("a", "2"), "1");
+        assert_eq!(ini[""].get_or("b", "2"), "2");
+        assert_eq!(ini[""].get_or("empty", "2"), "");
+    }
+
+    #[test]
+    fn get_or_else() {
+        let ini = Ini::from_str("a=1").unwrap();
+        assert_eq!(ini[""].get_or_else("a", || unreachable!()), "1");
+        assert_eq!(ini[""].get_or_else("b", || "2".into()), "2");
+    }
+}
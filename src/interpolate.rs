@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::{Error, Result},
+    Ini, Section,
+};
+
+impl Ini {
+    /// Replace `%(key)s` references in values with the values of other keys
+    /// in the same section, as in Python's `configparser`.
+    ///
+    /// References are resolved recursively, so a referenced value may itself
+    /// contain references, up to 10 levels deep. Write `%%` for a literal
+    /// `%`. A `%(` without a closing `)s` is kept as is. This fails with
+    /// `Error::MissingKey` if a referenced key does not exist, with
+    /// `Error::CyclicReference`, naming the first key found to repeat, if a
+    /// key refers back to itself directly or through other keys, and with
+    /// `Error::InterpolationDepth` if references are nested more deeply. On
+    /// failure, the config is left unchanged.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let mut ini = Ini::from_str("base=\"/srv\"\nlogs=\"%(base)s/logs\"").unwrap();
+    /// ini.interpolate().unwrap();
+    /// assert_eq!(ini[""]["logs"], "/srv/logs");
+    /// ```
+    pub fn interpolate(&mut self) -> Result<()> {
+        let mut interpolated = self.clone();
+        for ((name, section), target) in self.sections().zip(interpolated.sections_mut()) {
+            let mut resolver = Resolver {
+                section,
+                name,
+                resolved: HashMap::new(),
+                resolving: Vec::new(),
+            };
+            for value in target.values_mut() {
+                *value = resolver.value(value)?.0;
+            }
+        }
+        *self = interpolated;
+        Ok(())
+    }
+}
+
+/// Maximum number of nested references, as in Python's `configparser`.
+const MAX_DEPTH: usize = 10;
+
+/// Resolves the references in the values of one section.
+struct Resolver<'a> {
+    section: &'a Section,
+    name: &'a str,
+    /// Keys resolved so far, with their value and reference depth.
+    resolved: HashMap<String, (String, usize)>,
+    /// Keys being resolved, outermost first.
+    resolving: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// Replace the references in a value, returning the result and the
+    /// depth of the most deeply nested reference.
+    fn value(&mut self, value: &str) -> Result<(String, usize)> {
+        let mut out = String::new();
+        let mut depth = 0;
+        let mut rest = value;
+        while let Some(ix) = rest.find('%') {
+            out.push_str(&rest[..ix]);
+            rest = &rest[ix + 1..];
+            if let Some(after) = rest.strip_prefix('%') {
+                out.push('%');
+                rest = after;
+                continue;
+            }
+            let Some(len) = rest.strip_prefix('(').and_then(|after| after.find(")s")) else {
+                out.push('%');
+                continue;
+            };
+            let key = &rest[1..1 + len];
+            let (text, key_depth) = self.key(key)?;
+            if self.resolving.len() + key_depth + 1 > MAX_DEPTH {
+                return Err(self.depth_error(key));
+            }
+            out.push_str(&text);
+            depth = depth.max(key_depth + 1);
+            rest = &rest[len + 3..];
+        }
+        out.push_str(rest);
+        Ok((out, depth))
+    }
+
+    /// Resolve the value of a referenced key, reusing earlier results.
+    fn key(&mut self, key: &str) -> Result<(String, usize)> {
+        let referenced = self
+            .section
+            .get(key)
+            .ok_or_else(|| Error::MissingKey(key.into()))?;
+        let stored = self.section.stored_name(key);
+        if let Some(resolved) = self.resolved.get(&stored) {
+            return Ok(resolved.clone());
+        }
+        if self.resolving.contains(&stored) {
+            let section = self.name.into();
+            return Err(Error::CyclicReference {
+                section,
+                key: stored,
+            });
+        }
+        if self.resolving.len() >= MAX_DEPTH {
+            return Err(self.depth_error(key));
+        }
+        self.resolving.push(stored.clone());
+        let resolved = self.value(referenced)?;
+        self.resolving.pop();
+        self.resolved.insert(stored, resolved.clone());
+        Ok(resolved)
+    }
+
+    fn depth_error(&self, key: &str) -> Error {
+        Error::InterpolationDepth {
+            section: self.name.into(),
+            key: self.section.stored_name(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate() {
+        let text = r#"
+            base="/srv"
+            [paths]
+            base="/srv"
+            logs="%(base)s/logs"
+            archive="%(logs)s/old"
+            percent="100%% of %(base)s"
+            open="%(base"
+            "#;
+        let mut ini = Ini::from_str(text).unwrap();
+        ini.interpolate().unwrap();
+        assert_eq!(ini["paths"]["logs"], "/srv/logs");
+        assert_eq!(ini["paths"]["archive"], "/srv/logs/old");
+        assert_eq!(ini["paths"]["percent"], "100% of /srv");
+        assert_eq!(ini["paths"]["open"], "%(base");
+    }
+
+    #[test]
+    fn interpolate_other_section() {
+        let mut ini = Ini::from_str("base=\"/srv\"\n[paths]\nlogs=\"%(base)s/logs\"").unwrap();
+        assert_eq!(ini.interpolate(), Err(Error::MissingKey("base".into())));
+        assert_eq!(ini["paths"]["logs"], "%(base)s/logs");
+    }
+
+    #[test]
+    fn interpolate_cycle() {
+        let text = "[loop]\na=\"%(b)s\"\nb=\"x%(c)s\"\nc=\"%(a)s\"\nd=1";
+        let mut ini = Ini::from_str(text).unwrap();
+        let error = Error::CyclicReference {
+            section: "loop".into(),
+            key: "b".into(),
+        };
+        assert_eq!(ini.interpolate(), Err(error));
+        assert_eq!(ini["loop"]["a"], "%(b)s");
+
+        let mut ini = Ini::from_str("a=\"%(a)s\"").unwrap();
+        assert!(matches!(
+            ini.interpolate(),
+            Err(Error::CyclicReference { key, .. }) if key == "a"
+        ));
+    }
+
+    #[test]
+    fn interpolate_diamond() {
+        let mut ini = Ini::new();
+        for level in 0..9 {
+            let next = format!("%(k{})s", level + 1);
+            ini[""].insert(format!("k{level}"), format!("{next}{next}"));
+        }
+        ini[""].insert("k9".into(), "x".into());
+        ini[""].insert("top".into(), "%(k0)s".into());
+        ini.interpolate().unwrap();
+        assert_eq!(ini[""]["k9"], "x");
+        assert_eq!(ini[""]["k0"], "x".repeat(512));
+        assert_eq!(ini[""]["top"], "x".repeat(512));
+    }
+
+    #[test]
+    fn interpolate_depth() {
+        let mut ini = Ini::new();
+        for level in 0..11 {
+            let value = format!("%(k{})s", level + 1);
+            ini[""].insert(format!("k{level}"), value);
+        }
+        ini[""].insert("k11".into(), "x".into());
+        let error = Error::InterpolationDepth {
+            section: "".into(),
+            key: "k11".into(),
+        };
+        assert_eq!(ini.interpolate(), Err(error));
+        assert_eq!(ini[""]["k0"], "%(k1)s");
+
+        ini[""].remove("k0");
+        ini.interpolate().unwrap();
+        assert_eq!(ini[""]["k1"], "x");
+
+        // Keys resolved before the key that refers to them count toward the
+        // depth as well.
+        let mut ini = Ini::new();
+        ini[""].insert("k11".into(), "x".into());
+        for level in (0..11).rev() {
+            let value = format!("%(k{})s", level + 1);
+            ini[""].insert(format!("k{level}"), value);
+        }
+        let error = Error::InterpolationDepth {
+            section: "".into(),
+            key: "k2".into(),
+        };
+        assert_eq!(ini.interpolate(), Err(error));
+    }
+}
@@ -1,31 +1,85 @@
-use crate::error::{Error, Result};
+use std::{cell::OnceCell, collections::HashSet, mem::Discriminant};
+
+use crate::{
+    diagnostic::{Diagnostic, DiagnosticKind},
+    error::{Error, Result},
+    escape::unescape_quoted,
+    ParseOptions,
+};
 
 #[derive(PartialEq, Debug)]
 pub enum Token {
     LeftBracket,
     RightBracket,
     Equal,
+    Colon,
     Newline,
     String(String),
+    Comment(String),
+}
+
+/// A token with the byte offsets where it starts and ends in the text.
+#[derive(PartialEq, Debug)]
+pub struct Spanned {
+    pub token: Token,
+    pub span: (usize, usize),
 }
 
 pub struct Lexer<'a> {
     text: &'a str,
     pos: usize,
+    options: ParseOptions,
+    /// Byte offsets where each line starts, found when first needed.
+    line_starts: OnceCell<Vec<usize>>,
+    /// Whether non-fatal issues are collected.
+    pub collect_diagnostics: bool,
+    /// Non-fatal issues found so far, with the byte offset where each starts.
+    diagnostics: Vec<(usize, DiagnosticKind)>,
+    /// Offsets and kinds of the issues found so far, to skip repeats.
+    diagnosed: HashSet<(usize, Discriminant<DiagnosticKind>)>,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(text: &str) -> Lexer {
-        Lexer { text, pos: 0 }
+    #[cfg(test)]
+    pub fn new(text: &str) -> Lexer<'_> {
+        Lexer::with_options(text, &ParseOptions::default())
+    }
+
+    /// Create a lexer for a text, skipping a leading byte order mark.
+    pub fn with_options<'t>(text: &'t str, options: &ParseOptions) -> Lexer<'t> {
+        Lexer {
+            text: text.strip_prefix('\u{feff}').unwrap_or(text),
+            pos: 0,
+            options: options.clone(),
+            line_starts: OnceCell::new(),
+            collect_diagnostics: false,
+            diagnostics: Vec::new(),
+            diagnosed: HashSet::new(),
+        }
     }
 
     pub fn next(&mut self) -> Result<Option<Token>> {
-        use Token::*;
+        Ok(self.next_spanned()?.map(|spanned| spanned.token))
+    }
 
-        self.skip_whitespace();
+    /// Read the next token along with its span.
+    pub fn next_spanned(&mut self) -> Result<Option<Spanned>> {
+        self.skip_whitespace()?;
+        let start = self.pos;
+        let token = self.scan_token()?;
+        Ok(token.map(|token| Spanned {
+            token,
+            span: (start, self.pos),
+        }))
+    }
+
+    fn scan_token(&mut self) -> Result<Option<Token>> {
+        use Token::*;
 
         if let Some(len) = self.scan_comment() {
+            let comment = self.text[self.pos..self.pos + len].trim_end().to_string();
             self.pos += len;
+            return Ok(Some(Comment(comment)));
         }
 
         if self.pos >= self.text.len() {
@@ -47,23 +101,42 @@ impl<'a> Lexer<'a> {
             return Ok(Some(Equal));
         }
 
+        if self.scan_colon() {
+            self.pos += 1;
+            return Ok(Some(Colon));
+        }
+
         if let Some(len) = self.scan_newline() {
             self.pos += len;
             return Ok(Some(Newline));
         }
 
+        if let Some(len) = self.scan_triple_quote_string()? {
+            let string = &self.text[self.pos + 3..self.pos + 3 + len];
+            self.pos += len + 6;
+            return Ok(Some(String(string.into())));
+        }
+
         if let Some(len) = self.scan_quote_string()? {
-            let string = self.text[self.pos + 1..self.pos + 1 + len].replace(r#"\""#, "\"");
+            let string = unescape_quoted(&self.text[self.pos + 1..self.pos + 1 + len]);
             self.pos += len + 2;
             return Ok(Some(String(string)));
         }
 
-        let len = self.scan_string();
-        {
-            let string = &self.text[self.pos..self.pos + len];
-            self.pos += len;
-            return Ok(Some(String(string.into())));
+        if let Some(len) = self.scan_single_quote_string()? {
+            let string = self.text[self.pos + 1..self.pos + 1 + len].replace(r"\'", "'");
+            self.pos += len + 2;
+            return Ok(Some(String(string)));
         }
+
+        if self.options.line_continuations {
+            return Ok(Some(String(self.scan_continued_string()?)));
+        }
+
+        let len = self.scan_string();
+        let string = &self.text[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(Some(String(string.into())))
     }
 
     pub fn peek(&mut self) -> Result<Option<Token>> {
@@ -73,11 +146,106 @@ impl<'a> Lexer<'a> {
         token
     }
 
-    fn skip_whitespace(&mut self) {
+    /// Returns the line and column of a byte offset, both starting at 1.
+    ///
+    /// The offsets where lines start are found on the first call, so later
+    /// calls only scan the line that holds the offset.
+    pub fn position(&self, pos: usize) -> (usize, usize) {
+        let line_starts = self.line_starts.get_or_init(|| {
+            let newlines = self.text.match_indices('\n').map(|(ix, _)| ix + 1);
+            std::iter::once(0).chain(newlines).collect()
+        });
+        let line = line_starts.partition_point(|&start| start <= pos);
+        let column = self.text[line_starts[line - 1]..pos].chars().count() + 1;
+        (line, column)
+    }
+
+    /// Returns the byte offset where the next token starts.
+    pub fn next_token_pos(&self) -> usize {
+        let bytes = self.text.as_bytes();
+        let mut pos = self.pos;
+        while pos < self.text.len() && matches!(bytes[pos], b' ' | b'\t') {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Returns whether the next token is a quoted string, with either double
+    /// or single quotes.
+    pub fn next_is_quoted(&self) -> bool {
+        matches!(
+            self.text.as_bytes().get(self.next_token_pos()),
+            Some(b'"' | b'\'')
+        )
+    }
+
+    /// Move past the end of the line containing a byte offset.
+    pub fn skip_line(&mut self, pos: usize) {
+        self.pos = match self.text[pos..].find('\n') {
+            Some(ix) => pos + ix + 1,
+            None => self.text.len(),
+        };
+    }
+
+    /// Returns a parse error at a byte offset.
+    pub fn error_at(&self, pos: usize) -> Error {
+        let (line, column) = self.position(pos);
+        Error::Parse { line, column }
+    }
+
+    /// Record a diagnostic at a byte offset, if diagnostics are collected.
+    ///
+    /// Text may be scanned more than once because of lookahead, so a
+    /// diagnostic that was already recorded is ignored.
+    pub fn diagnose(&mut self, kind: DiagnosticKind, pos: usize) {
+        if self.collect_diagnostics && self.diagnosed.insert((pos, std::mem::discriminant(&kind))) {
+            self.diagnostics.push((pos, kind));
+        }
+    }
+
+    /// Take the diagnostics recorded so far, in text order.
+    ///
+    /// Offsets are converted to lines and columns in a single pass over the
+    /// text.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+        self.diagnosed.clear();
+        diagnostics.sort_by_key(|(pos, _)| *pos);
+        let (mut line, mut line_start, mut scanned) = (1, 0, 0);
+        diagnostics
+            .into_iter()
+            .map(|(pos, kind)| {
+                for (ix, _) in self.text[scanned..pos].match_indices('\n') {
+                    line += 1;
+                    line_start = scanned + ix + 1;
+                }
+                scanned = pos;
+                let column = self.text[line_start..pos].chars().count() + 1;
+                Diagnostic { kind, line, column }
+            })
+            .collect()
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
+        let start = self.pos;
         let bytes = self.text.as_bytes();
         while self.pos < self.text.len() && matches!(bytes[self.pos], b' ' | b'\t') {
+            if bytes[self.pos] == b'\t' && self.options.no_tabs {
+                let (line, column) = self.position(self.pos);
+                return Err(Error::Tab { line, column });
+            }
             self.pos += 1;
         }
+
+        if !self.collect_diagnostics {
+            return Ok(());
+        }
+        let after_content = start > 0 && bytes[start - 1] != b'\n';
+        let at_line_end = self.pos >= self.text.len() || self.scan_newline().is_some();
+        if self.pos > start && after_content && at_line_end {
+            self.diagnose(DiagnosticKind::TrailingWhitespace, start);
+        }
+        Ok(())
     }
 
     fn scan_left_bracket(&self) -> bool {
@@ -98,6 +266,12 @@ impl<'a> Lexer<'a> {
         current == b'='
     }
 
+    fn scan_colon(&self) -> bool {
+        assert!(self.pos < self.text.len());
+        let current = self.text.as_bytes()[self.pos];
+        current == b':'
+    }
+
     fn scan_newline(&self) -> Option<usize> {
         assert!(self.pos < self.text.len());
         let current = self.text.as_bytes()[self.pos];
@@ -115,8 +289,8 @@ impl<'a> Lexer<'a> {
             return None;
         }
         let bytes = self.text.as_bytes();
-        let current = bytes[self.pos];
-        if current == b';' || current == b'#' {
+        let current = self.text[self.pos..].chars().next()?;
+        if self.options.comment_prefixes.contains(&current) {
             let mut ix = self.pos;
             let mut len = 0;
             while ix < self.text.len() {
@@ -147,7 +321,7 @@ impl<'a> Lexer<'a> {
             if bytes[ix] == b'"' {
                 return Ok(Some(len));
             }
-            if self.text[ix..].starts_with(r#"\""#) {
+            if bytes[ix] == b'\\' {
                 ix += 2;
                 len += 2;
                 continue;
@@ -155,21 +329,90 @@ impl<'a> Lexer<'a> {
             ix += 1;
             len += 1;
         }
-        Err(Error::Parse)
+        Err(self.error_at(self.pos))
     }
 
-    fn scan_string(&self) -> usize {
+    /// Scan a string in triple double quotes, returning the length of its
+    /// contents.
+    ///
+    /// The contents are taken verbatim, including line breaks, and end at the
+    /// first `"""`.
+    fn scan_triple_quote_string(&self) -> Result<Option<usize>> {
+        assert!(self.pos < self.text.len());
+        let Some(contents) = self.text[self.pos..].strip_prefix(r#"""""#) else {
+            return Ok(None);
+        };
+        match contents.find(r#"""""#) {
+            Some(len) => Ok(Some(len)),
+            None => Err(self.error_at(self.pos)),
+        }
+    }
+
+    /// Scan a single-quoted string, returning the length of its contents.
+    ///
+    /// Only `\'` is an escape, for an embedded single quote. Double quotes
+    /// and other backslashes are part of the string as written.
+    fn scan_single_quote_string(&self) -> Result<Option<usize>> {
         assert!(self.pos < self.text.len());
         let bytes = self.text.as_bytes();
-        let mut ix = self.pos;
+        if bytes[self.pos] != b'\'' {
+            return Ok(None);
+        }
+        let contents = &bytes[self.pos + 1..];
         let mut len = 0;
+        while len < contents.len() {
+            match &contents[len..] {
+                [b'\'', ..] => return Ok(Some(len)),
+                [b'\\', b'\'', ..] => len += 2,
+                _ => len += 1,
+            }
+        }
+        Err(self.error_at(self.pos))
+    }
 
-        while ix < self.text.len() {
-            match bytes[ix] {
-                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' | b'-' => {
-                    len += 1;
-                    ix += 1;
-                }
+    /// Read a bare string that may continue onto following lines.
+    ///
+    /// A `\` before a line break joins the content of the next line, after
+    /// its indentation. A `\` at the end of the text is ignored, and `\\`
+    /// stands for a literal backslash.
+    fn scan_continued_string(&mut self) -> Result<std::string::String> {
+        let mut string = std::string::String::new();
+        while self.pos < self.text.len() {
+            let len = self.scan_string();
+            string.push_str(&self.text[self.pos..self.pos + len]);
+            self.pos += len;
+
+            let rest = &self.text[self.pos..];
+            if rest.starts_with(r"\\") {
+                string.push('\\');
+                self.pos += 2;
+                continue;
+            }
+            if !rest.starts_with('\\') {
+                break;
+            }
+            let start = self.pos;
+            self.pos += 1;
+            if self.pos >= self.text.len() {
+                break;
+            }
+            match self.scan_newline() {
+                Some(len) => self.pos += len,
+                None => return Err(self.error_at(start)),
+            }
+            self.skip_whitespace()?;
+        }
+        Ok(string)
+    }
+
+    fn scan_string(&self) -> usize {
+        assert!(self.pos < self.text.len());
+        let mut len = 0;
+
+        for c in self.text[self.pos..].chars() {
+            match c {
+                c if is_bare_char(c) => len += c.len_utf8(),
+                ';' | '#' if !self.options.comment_prefixes.contains(&c) => len += 1,
                 _ => break,
             }
         }
@@ -178,6 +421,11 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Returns whether a character may appear in a bare string.
+pub(crate) fn is_bare_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Token::*, *};
@@ -204,6 +452,13 @@ mod tests {
         assert_eq!(token, Some(Equal));
     }
 
+    #[test]
+    fn colon() {
+        let text = ":";
+        let token = Lexer::new(text).next().unwrap();
+        assert_eq!(token, Some(Colon));
+    }
+
     #[test]
     fn multiple_tokens() -> Result<()> {
         let text = "[]=";
@@ -265,6 +520,41 @@ mod tests {
         assert!(token.is_err());
     }
 
+    #[test]
+    fn triple_quote_string() -> Result<()> {
+        let text = "\"\"\"line one\n  \"two\" \\n\nthree\"\"\"\nb";
+        let mut lexer = Lexer::new(text);
+        let value = "line one\n  \"two\" \\n\nthree";
+        assert_eq!(lexer.next()?, Some(String(value.into())));
+        assert_eq!(lexer.next()?, Some(Newline));
+        assert_eq!(lexer.next()?, Some(String("b".into())));
+
+        assert!(Lexer::new("\"\"\"foo\nbar\"\"").next().is_err());
+        assert_eq!(Lexer::new("\"\"").next()?, Some(String("".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn single_quote_string() {
+        let text = "'hello wörld'";
+        let token = Lexer::new(text).next().unwrap();
+        assert_eq!(token, Some(String("hello wörld".into())));
+    }
+
+    #[test]
+    fn single_quote_escape() {
+        let text = r#"'it\'s "quoted" C:\dir'"#;
+        let token = Lexer::new(text).next().unwrap();
+        assert_eq!(token, Some(String(r#"it's "quoted" C:\dir"#.into())));
+    }
+
+    #[test]
+    fn single_quote_mismatched() {
+        assert!(Lexer::new("'foo").next().is_err());
+        assert!(Lexer::new(r"'foo\'").next().is_err());
+        assert!(Lexer::new(r#"'foo""#).next().is_err());
+    }
+
     #[test]
     fn section() -> Result<()> {
         let text = "[section]";
@@ -299,11 +589,56 @@ mod tests {
         assert_eq!(token, Some(String("foo".into())));
     }
 
+    #[test]
+    fn tab_whitespace() {
+        let text = "\tfoo";
+        let token = Lexer::new(text).next().unwrap();
+        assert_eq!(token, Some(String("foo".into())));
+    }
+
+    #[test]
+    fn no_tabs() {
+        let text = "[foo]\n\tbar=baz";
+        let options = ParseOptions::new().no_tabs(true);
+        let mut lexer = Lexer::with_options(text, &options);
+        let tokens: Result<Vec<_>> = std::iter::from_fn(|| lexer.next().transpose()).collect();
+        assert_eq!(tokens, Err(Error::Tab { line: 2, column: 1 }));
+    }
+
+    #[test]
+    fn trailing_whitespace_diagnostic() -> Result<()> {
+        let text = "[foo] \n  bar=baz\t\n  \n";
+        let mut lexer = Lexer::new(text);
+        while lexer.next()?.is_some() {}
+        assert!(lexer.take_diagnostics().is_empty());
+
+        let mut lexer = Lexer::new(text);
+        lexer.collect_diagnostics = true;
+        while lexer.next()?.is_some() {}
+        let positions: Vec<_> = lexer
+            .take_diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column))
+            .collect();
+        assert_eq!(positions, [(1, 6), (2, 10)]);
+        Ok(())
+    }
+
+    #[test]
+    fn position() {
+        let text = "foo\nbar=baz";
+        let lexer = Lexer::new(text);
+        assert_eq!(lexer.position(0), (1, 1));
+        assert_eq!(lexer.position(4), (2, 1));
+        assert_eq!(lexer.position(7), (2, 4));
+    }
+
     #[test]
     fn standalone_comment() {
         let text = "; comment";
-        let token = Lexer::new(text).next().unwrap();
-        assert!(token.is_none());
+        let mut lexer = Lexer::new(text);
+        assert_eq!(lexer.next().unwrap(), Some(Comment("; comment".into())));
+        assert_eq!(lexer.next().unwrap(), None);
     }
 
     #[test]
@@ -317,11 +652,109 @@ mod tests {
         assert_eq!(lexer.next()?, Some(LeftBracket));
         assert_eq!(lexer.next()?, Some(String("foo".into())));
         assert_eq!(lexer.next()?, Some(RightBracket));
+        assert_eq!(lexer.next()?, Some(Comment("; comment".into())));
         assert_eq!(lexer.next()?, Some(Newline));
         assert_eq!(lexer.next()?, Some(String("bar".into())));
         assert_eq!(lexer.next()?, Some(Equal));
         assert_eq!(lexer.next()?, Some(String("baz".into())));
+        assert_eq!(lexer.next()?, Some(Comment("; comment".into())));
+        assert_eq!(lexer.next()?, Some(Newline));
+        Ok(())
+    }
+
+    #[test]
+    fn comment_text() -> Result<()> {
+        let text = ";  standalone  \nfoo=bar #inline";
+        let mut lexer = Lexer::new(text);
+        assert_eq!(lexer.next()?, Some(Comment(";  standalone".into())));
+        assert_eq!(lexer.next()?, Some(Newline));
+        assert_eq!(lexer.next()?, Some(String("foo".into())));
+        assert_eq!(lexer.next()?, Some(Equal));
+        assert_eq!(lexer.next()?, Some(String("bar".into())));
+        assert_eq!(lexer.next()?, Some(Comment("#inline".into())));
+        assert_eq!(lexer.next()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn comment_prefixes() -> Result<()> {
+        let text = "; data\n# comment\n// other";
+        let options = ParseOptions::new().comment_prefixes(&['#', '/']);
+        let mut lexer = Lexer::with_options(text, &options);
+        assert_eq!(lexer.next()?, Some(String(";".into())));
+        assert_eq!(lexer.next()?, Some(String("data".into())));
+        assert_eq!(lexer.next()?, Some(Newline));
+        assert_eq!(lexer.next()?, Some(Comment("# comment".into())));
         assert_eq!(lexer.next()?, Some(Newline));
+        assert_eq!(lexer.next()?, Some(Comment("// other".into())));
+        assert_eq!(lexer.next()?, None);
+
+        let mut lexer = Lexer::new("a#b;c");
+        assert_eq!(lexer.next()?, Some(String("a".into())));
+        assert_eq!(lexer.next()?, Some(Comment("#b;c".into())));
+        assert_eq!(lexer.next()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn line_continuations() -> Result<()> {
+        let text = "a=one\\\n  two\\\r\n\tthree\nb=c\\\\d\\";
+        let options = ParseOptions::new().line_continuations(true);
+        let mut lexer = Lexer::with_options(text, &options);
+        assert_eq!(lexer.next()?, Some(String("a".into())));
+        assert_eq!(lexer.next()?, Some(Equal));
+        assert_eq!(lexer.next()?, Some(String("onetwothree".into())));
+        assert_eq!(lexer.next()?, Some(Newline));
+        assert_eq!(lexer.next()?, Some(String("b".into())));
+        assert_eq!(lexer.next()?, Some(Equal));
+        assert_eq!(lexer.next()?, Some(String("c\\d".into())));
+        assert_eq!(lexer.next()?, None);
+
+        let mut lexer = Lexer::with_options("a\\b", &options);
+        assert_eq!(lexer.next(), Err(Error::Parse { line: 1, column: 2 }));
+        Ok(())
+    }
+
+    #[test]
+    fn spans() -> Result<()> {
+        let mut lexer = Lexer::new("  foo");
+        let token = lexer.next_spanned()?.unwrap();
+        assert_eq!(token.token, String("foo".into()));
+        assert_eq!(token.span, (2, 5));
+        assert_eq!(lexer.next_spanned()?, None);
+
+        let mut lexer = Lexer::new("[a] ; c\nk = \"v w\"");
+        let mut spans = Vec::new();
+        while let Some(token) = lexer.next_spanned()? {
+            spans.push(token.span);
+        }
+        assert_eq!(
+            spans,
+            [
+                (0, 1),
+                (1, 2),
+                (2, 3),
+                (4, 7),
+                (7, 8),
+                (8, 9),
+                (10, 11),
+                (12, 17)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unicode_string() -> Result<()> {
+        let mut lexer = Lexer::new("имя=café_日本 ;x");
+        assert_eq!(lexer.next()?, Some(String("имя".into())));
+        assert_eq!(lexer.next()?, Some(Equal));
+        assert_eq!(lexer.next()?, Some(String("café_日本".into())));
+        assert_eq!(lexer.next()?, Some(Comment(";x".into())));
+        assert_eq!(lexer.next()?, None);
+
+        let mut lexer = Lexer::new("a→b");
+        assert_eq!(lexer.next()?, Some(String("a".into())));
         Ok(())
     }
 
@@ -329,6 +762,7 @@ mod tests {
     fn comment_win() -> Result<()> {
         let text = "; comment\r\nfoo";
         let mut lexer = Lexer::new(text);
+        assert_eq!(lexer.next()?, Some(Comment("; comment".into())));
         assert_eq!(lexer.next()?, Some(Newline));
         assert_eq!(lexer.next()?, Some(String("foo".into())));
         Ok(())
@@ -338,6 +772,7 @@ mod tests {
     fn comment_unix_style() -> Result<()> {
         let text = "# comment\nfoo";
         let mut lexer = Lexer::new(text);
+        assert_eq!(lexer.next()?, Some(Comment("# comment".into())));
         assert_eq!(lexer.next()?, Some(Newline));
         assert_eq!(lexer.next()?, Some(String("foo".into())));
         Ok(())
@@ -46,11 +46,23 @@
 //! foo=bar buz=bux # not ok
 //! ```
 //!
+//! A key with nothing after the `=`, as in `foo=`, has an empty value.
+//!
+//! A key name followed by `[]` adds a value to a list, whatever the policy for
+//! repeated keys. The values can be read with `Section::get_all`, and the last
+//! value is read as usual.
+//!
+//! ```ini
+//! path[]=lib
+//! path[]=bin
+//! ```
+//!
 //! Names and values can be bare strings or quoted strings.
 //!
-//! * Bare strings may be composed of ASCII alphanumeric characters and the
-//!   following symbols: `_.-`. All other characters are disallowed. Internal
-//!   whitespace is disallowed as well.
+//! * Bare strings may be composed of alphanumeric characters, including
+//!   non-ASCII letters and digits such as `é` or `ж`, and the following
+//!   symbols: `_.-`. All other characters are disallowed. Internal whitespace
+//!   is disallowed as well.
 //!
 //!   ```ini
 //!   foo=bar # ok
@@ -59,11 +71,33 @@
 //!
 //! * Quoted strings begin and end with double quotes (`"`) and can contain any
 //!   ASCII character including internal whitespace. Internal quotes must be
-//!   escaped.
+//!   escaped. The escapes `\n`, `\t`, `\r`, `\0`, and `\\` stand for a
+//!   newline, tab, carriage return, null character, and backslash. Any other
+//!   backslash is kept as written, so `"C:\dir"` holds `C:\dir`.
 //!
 //!   ```ini
 //!   foo="bar baz"
 //!   buz="bax\"boo"
+//!   lines="one\ntwo"
+//!   ```
+//!
+//! * Strings in triple double quotes (`"""`) may span several lines. Their
+//!   contents are kept as written, including line breaks, up to the closing
+//!   `"""`.
+//!
+//!   ```ini
+//!   cert="""-----BEGIN CERTIFICATE-----
+//!   MIIB...
+//!   -----END CERTIFICATE-----"""
+//!   ```
+//!
+//! * Single-quoted strings begin and end with `'`. The only escape is `\'`
+//!   for an embedded single quote, so double quotes and backslashes are kept
+//!   as written.
+//!
+//!   ```ini
+//!   foo='say "hi"'
+//!   bar='it\'s'
 //!   ```
 //!
 //! ## Sections
@@ -89,6 +123,22 @@
 //! baz=bux
 //! ```
 //!
+//! An empty section header, `[]`, returns to the default section, so the keys
+//! that follow it are added there.
+//!
+//! A section may be declared more than once. Keys from every declaration are
+//! collected into the same section, with later keys overwriting earlier keys
+//! of the same name. `ParseOptions::duplicate_keys` can keep the first value
+//! instead, keep every value, or reject repeated keys.
+//!
+//! ```ini
+//! [first]
+//! foo=bar
+//!
+//! [first]
+//! baz=bux
+//! ```
+//!
 //! ## Comments
 //!
 //! Both Windows (`;`) and Unix style comments (`#`) are supported.
@@ -110,10 +160,62 @@
 //! ; standalone comment
 //! foo=bar ; inline comment
 //! ```
+//!
+//! Comments are kept with the key or section header that follows them, or
+//! that they follow on the same line, and are written back when the config is
+//! serialized. Comments after the last key are written at the end.
+//!
+//! # Features
+//!
+//! The following optional features are available:
+//!
+//! * `std`, enabled by default, adds `Ini::from_file`, `Ini::from_reader`,
+//!   `Ini::write_to_file`, and `Ini::from_dir` for reading and writing files.
+//! * `base64` adds `Section::get_base64` and `Section::set_base64` for binary
+//!   values encoded as base64.
+//! * `percent-encoding` adds `Section::get_decoded` and `Section::set_encoded`
+//!   for percent-encoded values.
+//! * `serde` implements `Serialize` and `Deserialize` for `Ini` and `Section`,
+//!   as nested maps of names to values with the default section under "".
+//!   It also adds `Ini::deserialize_into` for reading a config into any type
+//!   that implements `Deserialize`.
 
+#[cfg(feature = "base64")]
+mod binary;
+#[cfg(feature = "serde")]
+mod deserialize;
+mod diagnostic;
+mod drift;
+mod env;
 mod error;
+mod escape;
+#[cfg(feature = "std")]
+mod file;
+mod frozen;
+mod hash;
+mod incremental;
 mod ini;
+mod interpolate;
 mod lexer;
+mod map;
+mod merge;
+mod options;
 mod parser;
+#[cfg(feature = "percent-encoding")]
+mod percent;
+mod query;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snapshot;
+mod typed;
+mod writer;
 
-pub use crate::ini::Ini;
+pub use crate::diagnostic::{Diagnostic, DiagnosticKind};
+pub use crate::drift::{Drift, DriftReport};
+pub use crate::error::{Error, Result};
+pub use crate::escape::{escape_value, unescape_value};
+pub use crate::frozen::FrozenIni;
+pub use crate::ini::{Entry, Ini, OccupiedEntry, Section, VacantEntry};
+pub use crate::options::{DuplicateKeyPolicy, LeadingDot, Newline, ParseOptions, WriteOptions};
+pub use crate::snapshot::{IniSnapshot, SectionSnapshot};
+pub use crate::typed::{FromFlagSection, FromIni, FromSection};
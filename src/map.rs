@@ -0,0 +1,209 @@
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// Map with string keys that iterates in insertion order.
+///
+/// Entries are stored in a vector, with a hash index from key to position for
+/// lookups. Replacing the value of an existing key keeps its position. Keys
+/// are reference counted so that the index and the entries share storage, and
+/// so that callers can share a key between maps.
+///
+/// A map can fold case, in which case keys that differ only in case are the
+/// same key. The entries keep the case of the key as first inserted.
+#[derive(Debug, Clone)]
+pub(crate) struct OrderedMap<V> {
+    entries: Vec<(Arc<str>, V)>,
+    index: HashMap<Arc<str>, usize>,
+    fold_case: bool,
+}
+
+impl<V> OrderedMap<V> {
+    pub fn new() -> Self {
+        Self::with_fold_case(false)
+    }
+
+    pub fn with_fold_case(fold_case: bool) -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+            fold_case,
+        }
+    }
+
+    pub fn fold_case(&self) -> bool {
+        self.fold_case
+    }
+
+    /// Returns the key under which a key is indexed.
+    fn index_key(fold_case: bool, key: &str) -> Cow<'_, str> {
+        match fold_case {
+            true => Cow::Owned(key.to_lowercase()),
+            false => Cow::Borrowed(key),
+        }
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        let key = Self::index_key(self.fold_case, key);
+        self.index.get(&*key).copied()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.position(key).map(|ix| &self.entries[ix].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.position(key).map(|ix| &mut self.entries[ix].1)
+    }
+
+    /// Returns the key as stored in the map.
+    pub fn key(&self, key: &str) -> Option<&Arc<str>> {
+        self.position(key).map(|ix| &self.entries[ix].0)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Insert a value, returning the previous value if the key existed.
+    pub fn insert(&mut self, key: impl Into<Arc<str>>, value: V) -> Option<V> {
+        let key = key.into();
+        match self.position(&key) {
+            Some(ix) => Some(std::mem::replace(&mut self.entries[ix].1, value)),
+            None => {
+                let index_key = match self.fold_case {
+                    true => key.to_lowercase().into(),
+                    false => key.clone(),
+                };
+                self.index.insert(index_key, self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove a key, returning its value if it existed.
+    ///
+    /// Later entries shift down to keep their order, so this takes linear
+    /// time.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let ix = self.index.remove(&*Self::index_key(self.fold_case, key))?;
+        let (_, value) = self.entries.remove(ix);
+        for (key, _) in &self.entries[ix..] {
+            let key = Self::index_key(self.fold_case, key);
+            *self.index.get_mut(&*key).unwrap() -= 1;
+        }
+        Some(value)
+    }
+
+    /// Change the key of an entry, keeping its position. Returns `false` if
+    /// the key does not exist.
+    ///
+    /// The new key must not belong to another entry.
+    pub fn rename(&mut self, from: &str, to: impl Into<Arc<str>>) -> bool {
+        let Some(ix) = self.index.remove(&*Self::index_key(self.fold_case, from)) else {
+            return false;
+        };
+        let to = to.into();
+        let index_key = match self.fold_case {
+            true => to.to_lowercase().into(),
+            false => to.clone(),
+        };
+        self.index.insert(index_key, ix);
+        self.entries[ix].0 = to;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Arc<str>, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[(Arc<str>, V)] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<(Arc<str>, V)> {
+        self.entries
+    }
+}
+
+impl<V> Default for OrderedMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: PartialEq> PartialEq for OrderedMap<V> {
+    /// Maps are equal if they hold the same entries, regardless of order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut map = OrderedMap::new();
+        map.insert("b", 1);
+        map.insert("a", 2);
+        map.insert("b", 3);
+        let entries: Vec<_> = map.iter().map(|(key, value)| (&**key, *value)).collect();
+        assert_eq!(entries, [("b", 3), ("a", 2)]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = OrderedMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("c", 3);
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.get("c"), Some(&3));
+        map.insert("a", 4);
+        let entries: Vec<_> = map.iter().map(|(key, value)| (&**key, *value)).collect();
+        assert_eq!(entries, [("b", 2), ("c", 3), ("a", 4)]);
+    }
+
+    #[test]
+    fn rename() {
+        let mut map = OrderedMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        assert!(map.rename("a", "c"));
+        assert!(!map.rename("a", "d"));
+        assert_eq!(map.get("a"), None);
+        let entries: Vec<_> = map.iter().map(|(key, value)| (&**key, *value)).collect();
+        assert_eq!(entries, [("c", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn fold_case() {
+        let mut map = OrderedMap::with_fold_case(true);
+        map.insert("Name", 1);
+        map.insert("NAME", 2);
+        map.insert("Other", 3);
+        assert_eq!(map.get("name"), Some(&2));
+        assert_eq!(map.key("name").map(|key| &**key), Some("Name"));
+        assert_eq!(map.remove("nAmE"), Some(2));
+        assert_eq!(map.get("other"), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{error::Result, DuplicateKeyPolicy, Error, Ini};
+
+impl Ini {
+    /// Overlay another config onto this one.
+    ///
+    /// Sections that exist in both configs have their keys merged, with
+    /// values from `other` replacing existing values. Sections that only
+    /// exist in `other` are added as they are, after the existing sections.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let mut config = Ini::from_str("[db]\nhost=localhost\nport=5432").unwrap();
+    /// let overrides = Ini::from_str("[db]\nhost=db.internal\n[log]\nlevel=warn").unwrap();
+    /// config.merge(overrides);
+    ///
+    /// assert_eq!(config["db"]["host"], "db.internal");
+    /// assert_eq!(config["db"]["port"], "5432");
+    /// assert_eq!(config["log"]["level"], "warn");
+    /// ```
+    pub fn merge(&mut self, other: Ini) {
+        self.merge_with(other, DuplicateKeyPolicy::Overwrite)
+            .expect("overwriting keys should not fail");
+    }
+
+    /// Overlay another config onto this one, resolving keys set in both
+    /// configs with the specified policy.
+    ///
+    /// This works like `merge`, except that a key that exists in both
+    /// configs is treated like a key declared twice while parsing, with the
+    /// value from `other` declared last. With `DuplicateKeyPolicy::Error`,
+    /// the first such key is returned as `Error::ConflictingKey` and this
+    /// config is left unchanged.
+    pub fn merge_with(&mut self, other: Ini, policy: DuplicateKeyPolicy) -> Result<()> {
+        if policy == DuplicateKeyPolicy::Error {
+            for (name, section) in other.sections() {
+                let Some(existing) = self.section(name) else {
+                    continue;
+                };
+                if let Some((key, _)) = section.iter().find(|(key, _)| existing.contains_key(key)) {
+                    return Err(Error::ConflictingKey {
+                        section: name.into(),
+                        key: key.into(),
+                    });
+                }
+            }
+        }
+
+        for (name, section) in other.sections() {
+            let Some(existing) = self.section_mut(name) else {
+                self.replace_section(name, section.clone());
+                continue;
+            };
+            for (key, value) in section.iter() {
+                let repeated = existing.contains_key(key);
+                match policy {
+                    DuplicateKeyPolicy::KeepFirst if repeated => continue,
+                    DuplicateKeyPolicy::Collect => {
+                        let earlier = section.earlier(key).iter().map(String::as_str);
+                        for value in earlier.chain([value]) {
+                            existing.append_shared(key.into(), value.into());
+                        }
+                    }
+                    _ => existing.insert(key.into(), value.into()),
+                }
+                if let Some(comment) = section.inline_comment(key) {
+                    existing.set_inline_comment(key, comment.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "a=1\nb=2\n[foo]\nc=3\nd=4\n[bar]\ne=5\n";
+    const OVERRIDES: &str = "b=20\n[foo]\nd=40\nf=6\n[baz]\ng=7\n";
+
+    fn merged(policy: DuplicateKeyPolicy) -> Result<Ini> {
+        let mut ini = Ini::from_str(BASE).unwrap();
+        ini.merge_with(Ini::from_str(OVERRIDES).unwrap(), policy)?;
+        Ok(ini)
+    }
+
+    #[test]
+    fn merge_overlapping() {
+        let mut ini = Ini::from_str(BASE).unwrap();
+        ini.merge(Ini::from_str(OVERRIDES).unwrap());
+        assert_eq!(
+            ini.to_string(),
+            "a=1\nb=20\n\n[foo]\nc=3\nd=40\nf=6\n\n[bar]\ne=5\n\n[baz]\ng=7\n"
+        );
+        assert_eq!(Ok(ini), merged(DuplicateKeyPolicy::Overwrite));
+    }
+
+    #[test]
+    fn merge_disjoint() {
+        let mut ini = Ini::from_str("[foo]\na=1\n").unwrap();
+        ini.merge(Ini::from_str("b=2\n[bar]\nc=3\n").unwrap());
+        assert_eq!(ini.to_string(), "b=2\n\n[foo]\na=1\n\n[bar]\nc=3\n");
+
+        let mut ini = Ini::new();
+        ini.merge(Ini::new());
+        assert!(ini.is_empty());
+    }
+
+    #[test]
+    fn merge_keep_first() {
+        let ini = merged(DuplicateKeyPolicy::KeepFirst).unwrap();
+        assert_eq!(ini[""]["b"], "2");
+        assert_eq!(ini["foo"]["d"], "4");
+        assert_eq!(ini["foo"]["f"], "6");
+        assert_eq!(ini["baz"]["g"], "7");
+    }
+
+    #[test]
+    fn merge_error() {
+        assert_eq!(
+            merged(DuplicateKeyPolicy::Error),
+            Err(Error::ConflictingKey {
+                section: "".into(),
+                key: "b".into(),
+            })
+        );
+
+        let mut ini = Ini::from_str("[foo]\na=1\n").unwrap();
+        let other = Ini::from_str("b=2\n[foo]\nc=3\n").unwrap();
+        ini.merge_with(other, DuplicateKeyPolicy::Error).unwrap();
+        assert_eq!(ini.to_string(), "b=2\n\n[foo]\na=1\nc=3\n");
+    }
+
+    #[test]
+    fn merge_collect() {
+        let ini = merged(DuplicateKeyPolicy::Collect).unwrap();
+        assert_eq!(ini[""].get_all("b"), ["2", "20"]);
+        assert_eq!(ini["foo"].get_all("d"), ["4", "40"]);
+        assert_eq!(ini["foo"]["f"], "6");
+    }
+
+    #[test]
+    fn merge_comments() {
+        let mut ini = Ini::from_str("[foo]\na=1 ; old\nb=2 ; kept\n").unwrap();
+        ini.merge(Ini::from_str("[foo]\na=10 ; new\nb=20\n").unwrap());
+        assert_eq!(ini.to_string(), "[foo]\na=10 ; new\nb=20 ; kept\n");
+    }
+}
@@ -0,0 +1,375 @@
+/// Options that control how INI text is parsed.
+///
+/// The default options accept the syntax described in the crate
+/// documentation. Individual extensions can be enabled with the builder
+/// methods.
+///
+/// ```
+/// use ini::{Ini, ParseOptions};
+///
+/// let options = ParseOptions::new().section_inheritance(true);
+/// let ini = Ini::from_str_with("
+///     [base]
+///     host=localhost
+///     [dev : base]
+///     port=8080
+/// ", &options).unwrap();
+///
+/// assert_eq!(ini["dev"]["host"], "localhost");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    pub(crate) section_inheritance: bool,
+    pub(crate) no_tabs: bool,
+    pub(crate) intern_names: bool,
+    pub(crate) reject_empty_values: bool,
+    pub(crate) global_section_header: Option<String>,
+    pub(crate) bare_keys: bool,
+    pub(crate) max_name_depth: Option<usize>,
+    pub(crate) comment_entries: bool,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) leading_dot: LeadingDot,
+    pub(crate) normalize_values: bool,
+    pub(crate) colon_delimiter: bool,
+    pub(crate) comment_prefixes: Vec<char>,
+    pub(crate) line_continuations: bool,
+    pub(crate) case_insensitive: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            section_inheritance: false,
+            no_tabs: false,
+            intern_names: false,
+            reject_empty_values: false,
+            global_section_header: None,
+            bare_keys: false,
+            max_name_depth: None,
+            comment_entries: false,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            leading_dot: LeadingDot::default(),
+            normalize_values: false,
+            colon_delimiter: false,
+            comment_prefixes: vec![';', '#'],
+            line_continuations: false,
+            case_insensitive: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recognize `[child : parent]` section headers.
+    ///
+    /// The child section starts with a copy of the keys that the parent
+    /// section holds at the point where the child is declared. Keys declared
+    /// in the child afterwards are added on top. The parent must be declared
+    /// before the child, otherwise parsing fails with
+    /// `Error::UndefinedParent`.
+    pub fn section_inheritance(mut self, enabled: bool) -> Self {
+        self.section_inheritance = enabled;
+        self
+    }
+
+    /// Reject tabs wherever whitespace is skipped, such as in indentation.
+    ///
+    /// Parsing fails with `Error::Tab` at the position of the first tab.
+    /// Tabs inside quoted strings are still allowed.
+    pub fn no_tabs(mut self, enabled: bool) -> Self {
+        self.no_tabs = enabled;
+        self
+    }
+
+    /// Share storage between keys with the same name.
+    ///
+    /// Configs that repeat the same key names across many sections use less
+    /// memory, at the cost of a hash lookup for each key while parsing.
+    /// Lookups and the rest of the API are unaffected.
+    pub fn intern_names(mut self, enabled: bool) -> Self {
+        self.intern_names = enabled;
+        self
+    }
+
+    /// Reject keys with an empty value, such as `foo=""`.
+    ///
+    /// Parsing fails with `Error::EmptyValue` naming the first such key.
+    pub fn reject_empty_values(mut self, enabled: bool) -> Self {
+        self.reject_empty_values = enabled;
+        self
+    }
+
+    /// Treat a named section header as the default section.
+    ///
+    /// Keys under a header with this name are added to the default section
+    /// (named ""), as if they appeared before any header. This reads files
+    /// written with the matching `WriteOptions::global_section_header`, such
+    /// as files that use a `[DEFAULT]` section.
+    pub fn global_section_header(mut self, name: Option<&str>) -> Self {
+        self.global_section_header = name.map(Into::into);
+        self
+    }
+
+    /// Accept keys written as a bare name without `=` or a value.
+    ///
+    /// Such keys are stored with an empty value. This is typically used for
+    /// sections of flags, which can be read with `Section::flag`.
+    pub fn bare_keys(mut self, enabled: bool) -> Self {
+        self.bare_keys = enabled;
+        self
+    }
+
+    /// Limit the number of dot-separated parts in section and key names.
+    ///
+    /// With a limit of 2, `[a.b]` is accepted but `[a.b.c]` fails with
+    /// `Error::NameDepth`. This bounds the depth of any tree built from dotted
+    /// names. By default there is no limit.
+    pub fn max_name_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_name_depth = depth;
+        self
+    }
+
+    /// Store standalone comments as keys of the section they appear in.
+    ///
+    /// Each comment on a line of its own becomes a key named `#1`, `#2`, and
+    /// so on, numbered from 1 in each section in the order they appear. The
+    /// value is the comment text without the comment character and
+    /// surrounding whitespace. Inline comments are kept as usual.
+    ///
+    /// So that these names never collide with real keys, keys whose names
+    /// start with `#` (which can only be written quoted) fail to parse with
+    /// `Error::ReservedName` in this mode. The entries are ordinary keys
+    /// otherwise, and are serialized as such.
+    pub fn comment_entries(mut self, enabled: bool) -> Self {
+        self.comment_entries = enabled;
+        self
+    }
+
+    /// Choose what happens when a key is declared more than once.
+    ///
+    /// This applies to a key declared again in the same section, including
+    /// in a repeated section header. Keys inherited from a parent section can
+    /// always be overwritten by the child. By default, the later value
+    /// overwrites the earlier one.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Keep the first value of a key declared more than once.
+    ///
+    /// This is shorthand for `duplicate_keys(DuplicateKeyPolicy::KeepFirst)`,
+    /// or `DuplicateKeyPolicy::Overwrite` if disabled, for files where the
+    /// first definition is authoritative.
+    pub fn first_key_wins(self, enabled: bool) -> Self {
+        self.duplicate_keys(match enabled {
+            true => DuplicateKeyPolicy::KeepFirst,
+            false => DuplicateKeyPolicy::Overwrite,
+        })
+    }
+
+    /// Choose how section and key names that start with `.` are treated.
+    ///
+    /// Some dialects reserve such names, as in `.hidden=1`, for metadata. By
+    /// default they are allowed like any other name.
+    pub fn leading_dot(mut self, policy: LeadingDot) -> Self {
+        self.leading_dot = policy;
+        self
+    }
+
+    /// Accept unquoted values made of several words, as in `name=John  Doe`.
+    ///
+    /// The words are joined with single spaces, so accidental runs of spaces
+    /// or tabs between them are collapsed. Surrounding whitespace is always
+    /// trimmed from unquoted values. Quoted values are left untouched, and a
+    /// value cannot mix quoted and unquoted words.
+    pub fn normalize_values(mut self, enabled: bool) -> Self {
+        self.normalize_values = enabled;
+        self
+    }
+
+    /// Accept `:` as well as `=` between a key and its value, as in
+    /// `key: value`.
+    ///
+    /// Both delimiters may be mixed in one file. A `:` inside a quoted value
+    /// is part of the value either way.
+    pub fn colon_delimiter(mut self, enabled: bool) -> Self {
+        self.colon_delimiter = enabled;
+        self
+    }
+
+    /// Set the characters that start a comment, by default `;` and `#`.
+    ///
+    /// A comment starts wherever one of these characters appears outside a
+    /// quoted string, and runs to the end of the line. If `;` or `#` is not a
+    /// comment character, it is read as part of names and values instead,
+    /// so with only `#`, the line `;key=a;b` holds the key `;key`.
+    pub fn comment_prefixes(mut self, prefixes: &[char]) -> Self {
+        self.comment_prefixes = prefixes.to_vec();
+        self
+    }
+
+    /// Join lines that end with `\` in unquoted names and values.
+    ///
+    /// A `\` directly before a line break continues the name or value with
+    /// the content of the next line, after its indentation, so `a=one\`
+    /// followed by `  two` reads as `onetwo`. A value may also start on the
+    /// next line, as in `a=\`. To keep a space where lines are joined, put
+    /// it before the `\` and enable `normalize_values`.
+    ///
+    /// In this mode, `\\` stands for a literal backslash, a `\` at the end
+    /// of the text is ignored, and any other `\` fails to parse.
+    pub fn line_continuations(mut self, enabled: bool) -> Self {
+        self.line_continuations = enabled;
+        self
+    }
+
+    /// Ignore case in section and key names.
+    ///
+    /// The parsed config is created with `Ini::new_case_insensitive`, so
+    /// lookups ignore case and names keep the case of their first
+    /// declaration. Headers and keys that differ only in case are repeats of
+    /// the same section or key. This has no effect when parsing into an
+    /// existing Ini with `Ini::parse_into`, which keeps that Ini's behavior.
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+}
+
+/// Treatment of section and key names that start with `.`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeadingDot {
+    /// Keep the name as written.
+    #[default]
+    Allow,
+    /// Remove the leading dots from the name, so `.hidden` is read as
+    /// `hidden`.
+    Strip,
+    /// Fail to parse with `Error::LeadingDot`.
+    Reject,
+}
+
+/// Treatment of a key declared more than once in a section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Replace the earlier value with the later one.
+    #[default]
+    Overwrite,
+    /// Ignore later values.
+    KeepFirst,
+    /// Fail to parse with `Error::DuplicateKey` at the repeated key.
+    Error,
+    /// Keep every value. Indexing the section returns the last value, and
+    /// serializing writes the key once for each value.
+    Collect,
+}
+
+/// Line ending used when serializing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    /// `\n`, as used on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as used on Windows.
+    Crlf,
+    /// The line ending of the platform the program runs on.
+    Native,
+}
+
+impl Newline {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::Crlf => "\r\n",
+            Newline::Native if cfg!(windows) => "\r\n",
+            Newline::Native => "\n",
+        }
+    }
+}
+
+/// Options that control how an `Ini` is serialized.
+///
+/// The default options produce the output of `Ini::to_string`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WriteOptions {
+    pub(crate) global_section_header: Option<String>,
+    pub(crate) spaced_delimiter: bool,
+    pub(crate) sorted: bool,
+    pub(crate) newline: Newline,
+}
+
+impl WriteOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the default section under a named header.
+    ///
+    /// By default, keys in the default section are written at the top of the
+    /// output without a header. With a header name such as `DEFAULT`, they are
+    /// written under `[DEFAULT]` instead. Parse the output with the matching
+    /// `ParseOptions::global_section_header` to read these keys back into the
+    /// default section.
+    pub fn global_section_header(mut self, name: Option<&str>) -> Self {
+        self.global_section_header = name.map(Into::into);
+        self
+    }
+
+    /// Write keys as `key = value`, with spaces around the `=`.
+    ///
+    /// By default keys are written in the compact form `key=value`. Both
+    /// forms parse to the same config.
+    pub fn spaced_delimiter(mut self, enabled: bool) -> Self {
+        self.spaced_delimiter = enabled;
+        self
+    }
+
+    /// Write sections and keys sorted by name rather than in insertion order.
+    ///
+    /// This gives the same output for the same content however it was
+    /// built, for stable diffs in version control. The default section is
+    /// still written first.
+    pub fn sorted(mut self, enabled: bool) -> Self {
+        self.sorted = enabled;
+        self
+    }
+
+    /// Set the line ending written after each line, by default `\n`.
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ini;
+
+    #[test]
+    fn default_options() {
+        let text = "a=1\n[foo]\nb=2 ; note";
+        assert_eq!(ParseOptions::new(), ParseOptions::default());
+        assert_eq!(
+            Ini::from_str_with(text, &ParseOptions::new()),
+            Ini::from_str(text)
+        );
+    }
+
+    #[test]
+    fn combined_options() {
+        let options = ParseOptions::new()
+            .colon_delimiter(true)
+            .case_insensitive(true)
+            .duplicate_keys(DuplicateKeyPolicy::KeepFirst);
+        let ini = Ini::from_str_with("[Foo]\nkey: 1\nKEY=2", &options).unwrap();
+        assert_eq!(ini["foo"]["key"], "1");
+        assert!(Ini::from_str("[Foo]\nkey: 1").is_err());
+    }
+}
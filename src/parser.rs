@@ -1,89 +1,391 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use crate::{
+    diagnostic::{Diagnostic, DiagnosticKind},
     error::Error,
-    lexer::{Lexer, Token},
-    Ini,
+    ini::comment_text,
+    lexer::{Lexer, Spanned, Token},
+    options::{DuplicateKeyPolicy, LeadingDot},
+    Ini, ParseOptions,
 };
 
 use crate::error::Result;
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
+    options: &'a ParseOptions,
+    /// Key names seen so far, when names are interned.
+    names: HashSet<Arc<str>>,
+    /// Byte offset of the first header for each section seen so far, when
+    /// diagnostics are collected.
+    headers: HashMap<String, usize>,
+    /// Standalone comments not yet attached to a key or section header.
+    pending_comments: Vec<String>,
+    /// Section and key names declared so far, with case folded if names
+    /// ignore case.
+    declared: HashSet<(String, String)>,
 }
 
 impl<'a> Parser<'a> {
     pub fn from_str(text: &str) -> Result<Ini> {
-        let lexer = Lexer::new(text);
-        let mut parser = Parser { lexer };
-        parser.ini()
+        Parser::from_str_with(text, &ParseOptions::default())
     }
 
-    fn ini(&mut self) -> Result<Ini> {
-        let mut ini = Ini::new();
-        let mut cur_section = "".to_string();
+    pub fn from_str_with(text: &str, options: &ParseOptions) -> Result<Ini> {
+        let mut ini = Ini::with_fold_case(options.case_insensitive);
+        Parser::parse_into(text, options, &mut ini)?;
+        Ok(ini)
+    }
+
+    /// Parse the text, also returning non-fatal issues found along the way.
+    pub fn from_str_with_diagnostics(
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<(Ini, Vec<Diagnostic>)> {
+        let mut ini = Ini::with_fold_case(options.case_insensitive);
+        let mut parser = Parser::new(text, options);
+        parser.lexer.collect_diagnostics = true;
+        parser.ini(&mut ini)?;
+        Ok((ini, parser.lexer.take_diagnostics()))
+    }
+
+    /// Parse the text, skipping malformed lines instead of stopping at the
+    /// first error.
+    pub fn from_str_lenient(text: &str, options: &ParseOptions) -> (Ini, Vec<Error>) {
+        let mut ini = Ini::with_fold_case(options.case_insensitive);
+        let errors = Parser::new(text, options).ini_lenient(&mut ini);
+        (ini, errors)
+    }
+
+    /// Parse the text, adding its sections and keys to an existing Ini.
+    pub fn parse_into(text: &str, options: &ParseOptions, ini: &mut Ini) -> Result<()> {
+        Parser::new(text, options).ini(ini)
+    }
+
+    fn new(text: &'a str, options: &'a ParseOptions) -> Self {
+        Parser {
+            lexer: Lexer::with_options(text, options),
+            options,
+            names: HashSet::new(),
+            headers: HashMap::new(),
+            pending_comments: Vec::new(),
+            declared: HashSet::new(),
+        }
+    }
 
+    fn ini(&mut self, ini: &mut Ini) -> Result<()> {
+        let mut cur_section = "".to_string();
         while let Some(token) = self.lexer.peek()? {
-            match token {
-                Token::Newline => {
-                    self.lexer.next()?;
-                    continue;
+            self.statement(token, ini, &mut cur_section)?;
+        }
+        ini.comments.append(&mut self.pending_comments);
+
+        Ok(())
+    }
+
+    /// Parse the whole text, skipping to the next line after each error.
+    fn ini_lenient(&mut self, ini: &mut Ini) -> Vec<Error> {
+        let mut cur_section = "".to_string();
+        let mut errors = Vec::new();
+        loop {
+            let start = self.lexer.next_token_pos();
+            let result = match self.lexer.peek() {
+                Ok(Some(token)) => self.statement(token, ini, &mut cur_section),
+                Ok(None) => break,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                errors.push(error);
+                self.lexer.skip_line(start);
+            }
+        }
+        ini.comments.append(&mut self.pending_comments);
+        errors
+    }
+
+    /// Parse a single newline, comment, section header, or key, starting
+    /// with the given token.
+    fn statement(&mut self, token: Token, ini: &mut Ini, cur_section: &mut String) -> Result<()> {
+        match token {
+            Token::Newline => {
+                self.lexer.next()?;
+            }
+            Token::LeftBracket => {
+                let pos = self.lexer.next_token_pos();
+                let (name, parent, comment) = self.section()?;
+                let name = self.section_name(self.leading_dot(name)?);
+                self.check_depth(&name)?;
+                self.check_duplicate_header(&name, pos);
+                let parent = parent
+                    .map(|parent| Ok(self.section_name(self.leading_dot(parent)?)))
+                    .transpose()?;
+                let inherited = match parent {
+                    Some(parent) => match ini.section(&parent) {
+                        Some(section) => Some(section.clone()),
+                        None => return Err(Error::UndefinedParent(parent)),
+                    },
+                    None => None,
+                };
+                ini.ensure_section(&name);
+                if let Some(inherited) = inherited {
+                    for (key, value) in inherited.iter() {
+                        ini[&name].insert(key.into(), value.into());
+                    }
                 }
-                Token::LeftBracket => {
-                    let name = self.section()?;
-                    ini.add_section(&name);
-                    cur_section = name;
+                let section = &mut ini[&name];
+                section.header_comments.append(&mut self.pending_comments);
+                if comment.is_some() {
+                    section.header_comment = comment;
                 }
-                Token::String(_) => {
-                    let (name, value) = self.key()?;
-                    ini[&cur_section].insert(name, value);
+                *cur_section = name;
+            }
+            Token::Comment(comment) => {
+                self.lexer.next()?;
+                if !self.options.comment_entries {
+                    self.pending_comments.push(comment);
+                    return Ok(());
                 }
-                _ => return Err(Error::Parse),
+                let section = &mut ini[cur_section.as_str()];
+                let count = section.iter().filter(|(name, _)| name.starts_with('#'));
+                let name = format!("#{}", count.count() + 1);
+                section.insert(name, comment_text(&comment).into());
             }
+            Token::String(_) => {
+                let pos = self.lexer.next_token_pos();
+                let (name, list, value, comment) = self.key()?;
+                let name = self.leading_dot(name)?;
+                if self.options.comment_entries && name.starts_with('#') {
+                    return Err(Error::ReservedName(name));
+                }
+                self.check_depth(&name)?;
+                if value.is_empty() && self.options.reject_empty_values {
+                    return Err(Error::EmptyValue(name));
+                }
+                let name = self.intern(name);
+                let declared = (self.fold(cur_section), self.fold(&name));
+                let repeated = !self.declared.insert(declared);
+                let section = &mut ini[cur_section.as_str()];
+                match self.options.duplicate_keys {
+                    _ if list => section.append_shared(name.clone(), value),
+                    DuplicateKeyPolicy::KeepFirst if repeated => {}
+                    DuplicateKeyPolicy::Error if repeated => {
+                        let (line, column) = self.lexer.position(pos);
+                        let key = name.to_string();
+                        return Err(Error::DuplicateKey { key, line, column });
+                    }
+                    DuplicateKeyPolicy::Collect if repeated => {
+                        section.append_shared(name.clone(), value)
+                    }
+                    _ => section.insert_shared(name.clone(), value),
+                }
+                section.add_leading_comments(&name, &mut self.pending_comments);
+                if let Some(comment) = comment {
+                    section.set_inline_comment(&name, comment);
+                }
+            }
+            _ => return Err(self.lexer.error_at(self.lexer.next_token_pos())),
         }
+        Ok(())
+    }
 
-        Ok(ini)
+    /// Convert a key name to shared storage, reusing the storage of an
+    /// identical name if names are interned.
+    fn intern(&mut self, name: String) -> Arc<str> {
+        if !self.options.intern_names {
+            return name.into();
+        }
+        if let Some(shared) = self.names.get(name.as_str()) {
+            return shared.clone();
+        }
+        let shared: Arc<str> = name.into();
+        self.names.insert(shared.clone());
+        shared
+    }
+
+    /// Check that a dotted name does not exceed the maximum depth.
+    fn check_depth(&self, name: &str) -> Result<()> {
+        match self.options.max_name_depth {
+            Some(max) if name.split('.').count() > max => Err(Error::NameDepth(name.into())),
+            _ => Ok(()),
+        }
     }
 
-    fn section(&mut self) -> Result<String> {
-        let left_br = self.lexer.next()?;
-        let name = self.lexer.next()?;
-        let right_br = self.lexer.next()?;
-        let newline = self.lexer.next()?;
-        match (left_br, name, right_br, newline) {
-            (
-                Some(Token::LeftBracket),
-                Some(Token::String(name)),
-                Some(Token::RightBracket),
-                Some(Token::Newline),
-            )
-            | (
-                Some(Token::LeftBracket),
-                Some(Token::String(name)),
-                Some(Token::RightBracket),
-                None,
-            ) => Ok(name),
-            _ => Err(Error::Parse),
-        }
-    }
-
-    fn key(&mut self) -> Result<(String, String)> {
-        let name = self.lexer.next()?;
-        let equal = self.lexer.next()?;
-        let value = self.lexer.next()?;
-        let newline = self.lexer.next()?;
-        match (name, equal, value, newline) {
-            (
-                Some(Token::String(name)),
-                Some(Token::Equal),
-                Some(Token::String(value)),
-                Some(Token::Newline),
-            )
-            | (Some(Token::String(name)), Some(Token::Equal), Some(Token::String(value)), None) => {
-                if name.is_empty() {
-                    return Err(Error::Parse);
+    /// Append the remaining unquoted words of a value, separated by single
+    /// spaces.
+    ///
+    /// Fails at a character that cannot appear in a bare string, since the
+    /// lexer reads it as an empty word without moving past it.
+    fn join_words(&mut self, value: &mut String) -> Result<()> {
+        while !self.lexer.next_is_quoted() {
+            match self.lexer.peek()? {
+                Some(Token::String(word)) if word.is_empty() => {
+                    return Err(self.lexer.error_at(self.lexer.next_token_pos()));
                 }
-                Ok((name, value))
+                Some(Token::String(word)) => {
+                    self.lexer.next()?;
+                    value.push(' ');
+                    value.push_str(&word);
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the leading dot policy to a section or key name.
+    fn leading_dot(&self, name: String) -> Result<String> {
+        if !name.starts_with('.') {
+            return Ok(name);
+        }
+        match self.options.leading_dot {
+            LeadingDot::Allow => Ok(name),
+            LeadingDot::Strip => Ok(name.trim_start_matches('.').into()),
+            LeadingDot::Reject => Err(Error::LeadingDot(name)),
+        }
+    }
+
+    /// Record a diagnostic if a section header was already seen.
+    fn check_duplicate_header(&mut self, name: &str, pos: usize) {
+        if !self.lexer.collect_diagnostics {
+            return;
+        }
+        let name = self.fold(name);
+        match self.headers.get(&name) {
+            Some(&first) => {
+                let (first_line, first_column) = self.lexer.position(first);
+                let kind = DiagnosticKind::DuplicateSection {
+                    name,
+                    first_line,
+                    first_column,
+                };
+                self.lexer.diagnose(kind, pos);
+            }
+            None => {
+                self.headers.insert(name, pos);
+            }
+        }
+    }
+
+    /// Fold the case of a name if names ignore case, for comparing names.
+    fn fold(&self, name: &str) -> String {
+        match self.options.case_insensitive {
+            true => name.to_lowercase(),
+            false => name.into(),
+        }
+    }
+
+    /// Map a section name from a header to the name it is stored under.
+    fn section_name(&self, name: String) -> String {
+        match &self.options.global_section_header {
+            Some(header) if *header == name => "".into(),
+            _ => name,
+        }
+    }
+
+    /// Parse a section header, returning the section name, the name of the
+    /// parent section if one was declared, and the inline comment if any.
+    fn section(&mut self) -> Result<(String, Option<String>, Option<String>)> {
+        let (pos, left_br) = self.next_at()?;
+        if left_br != Some(Token::LeftBracket) {
+            return Err(self.lexer.error_at(pos));
+        }
+        // An empty header, `[]`, returns to the default section.
+        let name = match self.lexer.peek()? {
+            Some(Token::RightBracket) => String::new(),
+            _ => match self.next_at()? {
+                (_, Some(Token::String(name))) => name,
+                (pos, _) => return Err(self.lexer.error_at(pos)),
+            },
+        };
+
+        let mut parent = None;
+        if self.options.section_inheritance && self.lexer.peek()? == Some(Token::Colon) {
+            self.lexer.next()?;
+            match self.next_at()? {
+                (_, Some(Token::String(name))) if !name.is_empty() => parent = Some(name),
+                (pos, _) => return Err(self.lexer.error_at(pos)),
+            }
+        }
+
+        let (pos, right_br) = self.next_at()?;
+        if right_br != Some(Token::RightBracket) {
+            return Err(self.lexer.error_at(pos));
+        }
+        let (comment, (pos, end)) = self.inline_comment()?;
+        match end {
+            Some(Token::Newline) | None => Ok((name, parent, comment)),
+            _ => Err(self.lexer.error_at(pos)),
+        }
+    }
+
+    /// Parse a key, returning its name, whether it has the `[]` list suffix,
+    /// its value, and the inline comment if any.
+    fn key(&mut self) -> Result<(String, bool, String, Option<String>)> {
+        let name = match self.next_at()? {
+            (_, Some(Token::String(name))) if !name.is_empty() => name,
+            (pos, _) => return Err(self.lexer.error_at(pos)),
+        };
+        let list = self.lexer.peek()? == Some(Token::LeftBracket);
+        if list {
+            self.lexer.next()?;
+            match self.next_at()? {
+                (_, Some(Token::RightBracket)) => {}
+                (pos, _) => return Err(self.lexer.error_at(pos)),
             }
-            _ => Err(Error::Parse),
+        }
+        let bare_end = matches!(
+            self.lexer.peek()?,
+            Some(Token::Newline | Token::Comment(_)) | None
+        );
+        if self.options.bare_keys && bare_end && !list {
+            let (comment, _) = self.inline_comment()?;
+            return Ok((name, list, String::new(), comment));
+        }
+        match self.next_at()? {
+            (_, Some(Token::Equal)) => {}
+            (_, Some(Token::Colon)) if self.options.colon_delimiter => {}
+            (pos, _) => return Err(self.lexer.error_at(pos)),
+        }
+        let quoted = self.lexer.next_is_quoted();
+        let mut value = match self.lexer.peek()? {
+            Some(Token::Newline | Token::Comment(_)) | None => String::new(),
+            _ => match self.next_at()? {
+                (_, Some(Token::String(value))) => value,
+                (pos, _) => return Err(self.lexer.error_at(pos)),
+            },
+        };
+        if self.options.normalize_values && !quoted {
+            self.join_words(&mut value)?;
+        }
+        match self.inline_comment()? {
+            (comment, (_, Some(Token::Newline) | None)) => Ok((name, list, value, comment)),
+            (_, (pos, Some(Token::Equal))) => {
+                let (line, column) = self.lexer.position(pos);
+                Err(Error::UnexpectedEqual { line, column })
+            }
+            (_, (pos, _)) => Err(self.lexer.error_at(pos)),
+        }
+    }
+
+    /// Read an inline comment if the next token is one, returning it with its
+    /// prefix along with the token that follows and its position.
+    #[allow(clippy::type_complexity)]
+    fn inline_comment(&mut self) -> Result<(Option<String>, (usize, Option<Token>))> {
+        match self.next_at()? {
+            (_, Some(Token::Comment(comment))) => Ok((Some(comment), self.next_at()?)),
+            next => Ok((None, next)),
+        }
+    }
+
+    /// Read the next token along with the byte offset where it starts.
+    fn next_at(&mut self) -> Result<(usize, Option<Token>)> {
+        let pos = self.lexer.next_token_pos();
+        match self.lexer.next_spanned()? {
+            Some(Spanned { token, span }) => Ok((span.0, Some(token))),
+            None => Ok((pos, None)),
         }
     }
 }
@@ -146,6 +448,236 @@ mod tests {
         assert_eq!(ini, Ok(expected));
     }
 
+    #[test]
+    fn repeated_section() {
+        let text = r"
+        [foo]
+        a=1
+        b=2
+        [bar]
+        [foo]
+        b=3
+        ";
+        let ini = Parser::from_str(text).unwrap();
+        assert_eq!(ini["foo"]["a"], "1");
+        assert_eq!(ini["foo"]["b"], "3");
+    }
+
+    #[test]
+    fn repeated_section_policies() {
+        let text = "[x]\na=1\nb=2\n[y]\n[x]\nb=3\nc=4";
+        let ini = Parser::from_str(text).unwrap();
+        assert_eq!(ini.to_string(), "[x]\na=1\nb=3\nc=4\n\n[y]\n");
+
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::KeepFirst);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini.to_string(), "[x]\na=1\nb=2\nc=4\n\n[y]\n");
+
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let error = Parser::from_str_with(text, &options).unwrap_err();
+        assert!(matches!(error, Error::DuplicateKey { key, line: 6, .. } if key == "b"));
+    }
+
+    #[test]
+    fn repeated_key_last_wins() {
+        let ini = Parser::from_str("[foo]\na=1\n[bar]\n[foo]\na=2").unwrap();
+        assert_eq!(ini["foo"]["a"], "2");
+    }
+
+    #[test]
+    fn first_key_wins() {
+        let options = ParseOptions::new()
+            .first_key_wins(true)
+            .section_inheritance(true);
+        let text = "[base]\na=1\n[foo : base]\na=2\na=3\n[bar]\n[foo]\na=4\nb=5";
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["foo"]["a"], "2");
+        assert_eq!(ini["foo"]["b"], "5");
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let text = "[base]\na=0\n[foo : base]\na=1\nb=2\n[foo]\na=3";
+        let options = |policy| {
+            ParseOptions::new()
+                .section_inheritance(true)
+                .duplicate_keys(policy)
+        };
+        let parse = |policy| Parser::from_str_with(text, &options(policy));
+
+        assert_eq!(
+            parse(DuplicateKeyPolicy::Overwrite).unwrap()["foo"]["a"],
+            "3"
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::KeepFirst).unwrap()["foo"]["a"],
+            "1"
+        );
+        let error = Error::DuplicateKey {
+            key: "a".into(),
+            line: 7,
+            column: 1,
+        };
+        assert_eq!(parse(DuplicateKeyPolicy::Error), Err(error));
+        let ini = parse(DuplicateKeyPolicy::Collect).unwrap();
+        assert_eq!(ini["foo"]["a"], "3");
+        assert_eq!(ini["foo"].earlier("a"), ["1"]);
+        let expected = "[base]\na=0\n\n[foo]\na=1\na[]=3\nb=2\n";
+        assert_eq!(ini.to_string(), expected);
+        assert_eq!(Parser::from_str(expected), Ok(ini));
+    }
+
+    #[test]
+    fn line_continuations() {
+        let text = "[foo]\na=\\\n  one \\\n  two \\\n  three\nb=2";
+        let options = ParseOptions::new()
+            .line_continuations(true)
+            .normalize_values(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["foo"]["a"], "one two three");
+        assert_eq!(ini["foo"]["b"], "2");
+        assert!(Parser::from_str(text).is_err());
+    }
+
+    #[test]
+    fn comment_prefixes() {
+        let text = "a=x;y # comment\n; b=1";
+        let ini = Parser::from_str(text).unwrap();
+        assert_eq!(ini[""]["a"], "x");
+        assert!(!ini[""].contains_key("b"));
+
+        let options = ParseOptions::new().comment_prefixes(&['#']);
+        let ini = Parser::from_str_with("a=x;y # comment\n;b=1", &options).unwrap();
+        assert_eq!(ini[""]["a"], "x;y");
+        assert_eq!(ini[""][";b"], "1");
+    }
+
+    #[test]
+    fn colon_delimiter() {
+        let text = "a: 1\n[foo]\nb = 2\nc:\"x:y\"";
+        let options = ParseOptions::new().colon_delimiter(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini["foo"]["b"], "2");
+        assert_eq!(ini["foo"]["c"], "x:y");
+        let error = Parser::from_str(text);
+        assert_eq!(error, Err(Error::Parse { line: 1, column: 2 }));
+    }
+
+    #[test]
+    fn normalize_values_invalid_word() {
+        let options = ParseOptions::new().normalize_values(true);
+        let result = Parser::from_str_with("a=one @", &options);
+        assert_eq!(result, Err(Error::Parse { line: 1, column: 7 }));
+        let result = Parser::from_str_with("a=one two @\nb=1", &options);
+        assert_eq!(
+            result,
+            Err(Error::Parse {
+                line: 1,
+                column: 11
+            })
+        );
+    }
+
+    #[test]
+    fn normalize_values() {
+        let text = "a =  one   two\tthree  \nb=\"  one   two \"\nc=one";
+        let options = ParseOptions::new().normalize_values(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini[""]["a"], "one two three");
+        assert_eq!(ini[""]["b"], "  one   two ");
+        assert_eq!(ini[""]["c"], "one");
+        assert_eq!(
+            Parser::from_str(text),
+            Err(Error::Parse {
+                line: 1,
+                column: 12
+            })
+        );
+
+        let mixed = Parser::from_str_with("a=one \"two\"", &options);
+        assert_eq!(mixed, Err(Error::Parse { line: 1, column: 7 }));
+    }
+
+    #[test]
+    fn leading_dot() {
+        let text = "[.foo : .bar]\n.a=1";
+        let options = ParseOptions::new().section_inheritance(true);
+        let allow = options.clone().leading_dot(LeadingDot::Allow);
+        let ini = Parser::from_str_with("[.bar]\n[.foo : .bar]\n.a=1", &allow).unwrap();
+        assert_eq!(ini[".foo"][".a"], "1");
+
+        let strip = options.clone().leading_dot(LeadingDot::Strip);
+        let ini = Parser::from_str_with("[bar]\n[..foo : .bar]\n.a=1", &strip).unwrap();
+        assert_eq!(ini["foo"]["a"], "1");
+
+        let reject = options.leading_dot(LeadingDot::Reject);
+        let ini = Parser::from_str_with(text, &reject);
+        assert_eq!(ini, Err(Error::LeadingDot(".foo".into())));
+        let ini = Parser::from_str_with("[foo]\n.a=1", &reject);
+        assert_eq!(ini, Err(Error::LeadingDot(".a".into())));
+        let ini = Parser::from_str_with("[foo]\na.b=1", &reject);
+        assert_eq!(ini.unwrap()["foo"]["a.b"], "1");
+    }
+
+    #[test]
+    fn repeated_section_diagnostic() {
+        let text = "[foo]\na=1\n[bar]\n  [foo] \nb=2";
+        let (ini, diagnostics) =
+            Parser::from_str_with_diagnostics(text, &ParseOptions::new()).unwrap();
+        assert_eq!(ini["foo"]["a"], "1");
+        assert_eq!(ini["foo"]["b"], "2");
+        let kinds: Vec<_> = diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.kind, diagnostic.line, diagnostic.column))
+            .collect();
+        let duplicate = DiagnosticKind::DuplicateSection {
+            name: "foo".into(),
+            first_line: 1,
+            first_column: 1,
+        };
+        assert_eq!(
+            kinds,
+            [
+                (duplicate, 4, 3),
+                (DiagnosticKind::TrailingWhitespace, 4, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let text = "a=1\n[foo]\nb=2";
+        let with_bom = format!("\u{feff}{text}");
+        assert_eq!(Ini::from_str(&with_bom), Ini::from_str(text));
+        assert_eq!(
+            Ini::from_str("\u{feff}a b"),
+            Err(Error::Parse { line: 1, column: 3 })
+        );
+    }
+
+    #[test]
+    fn unicode_names() {
+        let ini = Ini::from_str("[données]\nclé=été\n[東京]\n駅=新宿").unwrap();
+        assert_eq!(ini["données"]["clé"], "été");
+        assert_eq!(ini["東京"]["駅"], "新宿");
+        assert_eq!(ini.to_string(), "[données]\nclé=été\n\n[東京]\n駅=新宿\n");
+        assert_eq!(
+            Ini::from_str("é=a b"),
+            Err(Error::Parse { line: 1, column: 5 })
+        );
+    }
+
+    #[test]
+    fn parse_error_position() {
+        let error = |line, column| Err(Error::Parse { line, column });
+        assert_eq!(Parser::from_str("[foo\n]"), error(1, 5));
+        assert_eq!(Parser::from_str("a=1\n  [foo] b=2"), error(2, 9));
+        assert_eq!(Parser::from_str("a=1\nb\n"), error(2, 2));
+        assert_eq!(Parser::from_str("a=1\n]"), error(2, 1));
+        assert_eq!(Parser::from_str("a=1\nb==\n"), error(2, 3));
+    }
+
     #[test]
     fn keys_on_same_line() {
         let text = "bar=baz qux=quux";
@@ -153,6 +685,18 @@ mod tests {
         assert!(ini.is_err());
     }
 
+    #[test]
+    fn equal_in_value() {
+        let text = "[foo]\nbar=baz=qux";
+        let ini = Parser::from_str(text);
+        assert_eq!(ini, Err(Error::UnexpectedEqual { line: 2, column: 8 }));
+        let message = ini.unwrap_err().to_string();
+        assert_eq!(
+            message,
+            "unexpected `=` in value at line 2, column 8; quote the value"
+        );
+    }
+
     #[test]
     fn sections_on_same_line() {
         let text = "[foo] [bar]";
@@ -197,6 +741,192 @@ mod tests {
         assert_eq!(ini[""]["foo bar"], "baz");
     }
 
+    #[test]
+    fn section_inheritance() {
+        let text = r"
+        [base]
+        host=localhost
+        port=80
+        [dev : base]
+        port=8080
+        ";
+        let options = ParseOptions::new().section_inheritance(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["dev"]["host"], "localhost");
+        assert_eq!(ini["dev"]["port"], "8080");
+        assert_eq!(ini["base"]["port"], "80");
+    }
+
+    #[test]
+    fn section_inheritance_undeclared_parent() {
+        let text = r"
+        [dev : base]
+        [base]
+        ";
+        let options = ParseOptions::new().section_inheritance(true);
+        let ini = Parser::from_str_with(text, &options);
+        assert_eq!(ini, Err(Error::UndefinedParent("base".into())));
+    }
+
+    #[test]
+    fn section_inheritance_disabled() {
+        let text = "[dev : base]";
+        let ini = Parser::from_str(text);
+        assert!(ini.is_err());
+    }
+
+    #[test]
+    fn no_tabs() {
+        let text = "[foo]\n\tbar=baz";
+        let options = ParseOptions::new().no_tabs(true);
+        let ini = Parser::from_str_with(text, &options);
+        assert_eq!(ini, Err(Error::Tab { line: 2, column: 1 }));
+        assert!(Parser::from_str(text).is_ok());
+    }
+
+    #[test]
+    fn intern_names() {
+        let text = r"
+        [foo]
+        name=1
+        [bar]
+        name=2
+        ";
+        let shared = |options: &ParseOptions| {
+            let ini = Parser::from_str_with(text, options).unwrap();
+            let foo = ini["foo"].shared_names().next().unwrap().clone();
+            let bar = ini["bar"].shared_names().next().unwrap().clone();
+            Arc::ptr_eq(&foo, &bar)
+        };
+        assert!(shared(&ParseOptions::new().intern_names(true)));
+        assert!(!shared(&ParseOptions::new()));
+    }
+
+    #[test]
+    fn reject_empty_values() {
+        let text = r#"foo="""#;
+        let options = ParseOptions::new().reject_empty_values(true);
+        let ini = Parser::from_str_with(text, &options);
+        assert_eq!(ini, Err(Error::EmptyValue("foo".into())));
+        assert!(Parser::from_str(text).is_ok());
+    }
+
+    #[test]
+    fn empty_section_header() {
+        let ini = Parser::from_str("a=1\n[named]\nb=2\n[]\nc=3\n[ ] ; back\nd=4").unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert_eq!(ini[""]["c"], "3");
+        assert_eq!(ini[""]["d"], "4");
+        assert_eq!(ini["named"].iter().collect::<Vec<_>>(), [("b", "2")]);
+        assert_eq!(ini.len(), 2);
+        assert!(Parser::from_str("[\n]").is_err());
+    }
+
+    #[test]
+    fn triple_quotes() {
+        let text = "[tls]\ncert=\"\"\"-----BEGIN-----\nMIIB\n-----END-----\"\"\" ; pem\nkey=a";
+        let ini = Parser::from_str(text).unwrap();
+        assert_eq!(ini["tls"]["cert"], "-----BEGIN-----\nMIIB\n-----END-----");
+        assert_eq!(ini["tls"]["key"], "a");
+        assert_eq!(
+            Parser::from_str("a=1\nb=\"\"\"x\ny"),
+            Err(Error::Parse { line: 2, column: 3 })
+        );
+    }
+
+    #[test]
+    fn single_quotes() {
+        let ini = Parser::from_str("['my section']\n'a key'='say \"hi\"'\nb=' x '").unwrap();
+        assert_eq!(ini["my section"]["a key"], "say \"hi\"");
+        assert_eq!(ini["my section"]["b"], " x ");
+
+        let options = ParseOptions::new().normalize_values(true);
+        let ini = Parser::from_str_with("a='  one   two '", &options).unwrap();
+        assert_eq!(ini[""]["a"], "  one   two ");
+    }
+
+    #[test]
+    fn list_keys() {
+        let ini = Parser::from_str("[foo]\npath[]=lib\npath [ ] = bin ; note\nother=1").unwrap();
+        assert_eq!(ini["foo"].get_all("path"), ["lib", "bin"]);
+        assert_eq!(ini["foo"]["path"], "bin");
+        assert_eq!(ini["foo"].comment_for("path"), Some("note"));
+        let text = ini.to_string();
+        assert_eq!(text, "[foo]\npath=lib\npath[]=bin ; note\nother=1\n");
+        assert_eq!(Parser::from_str(&text), Ok(ini));
+
+        let ini = Parser::from_str("a=1\na[]=2\na[]=3\nb[]=4\nb=5").unwrap();
+        assert_eq!(ini[""].get_all("a"), ["1", "2", "3"]);
+        assert_eq!(ini[""].get_all("b"), ["5"]);
+        assert_eq!(Parser::from_str(&ini.to_string()), Ok(ini));
+
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let ini = Parser::from_str_with("a[]=1\na[]=2", &options).unwrap();
+        assert_eq!(ini[""].get_all("a"), ["1", "2"]);
+
+        assert!(Parser::from_str("a[=1").is_err());
+        assert!(Parser::from_str("a[]").is_err());
+    }
+
+    #[test]
+    fn empty_values() {
+        let ini = Parser::from_str("[foo]\na=\nb = ; unset\nc=").unwrap();
+        assert_eq!(ini["foo"]["a"], "");
+        assert_eq!(ini["foo"]["b"], "");
+        assert_eq!(ini["foo"]["c"], "");
+        assert_eq!(ini.to_string(), "[foo]\na=\"\"\nb=\"\" ; unset\nc=\"\"\n");
+
+        let options = ParseOptions::new().reject_empty_values(true);
+        let ini = Parser::from_str_with("a=\n", &options);
+        assert_eq!(ini, Err(Error::EmptyValue("a".into())));
+        assert!(Parser::from_str("[foo]\na\n").is_err());
+    }
+
+    #[test]
+    fn bare_keys() {
+        let text = "[features]\nfast\nsafe=off";
+        let options = ParseOptions::new().bare_keys(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        assert_eq!(ini["features"]["fast"], "");
+        assert_eq!(ini["features"]["safe"], "off");
+        assert!(Parser::from_str(text).is_err());
+    }
+
+    #[test]
+    fn max_name_depth() {
+        let options = ParseOptions::new().max_name_depth(Some(2));
+        assert!(Parser::from_str_with("[a.b]\nc.d=1", &options).is_ok());
+        let ini = Parser::from_str_with("[a.b.c]", &options);
+        assert_eq!(ini, Err(Error::NameDepth("a.b.c".into())));
+        let ini = Parser::from_str_with("a.b.c=1", &options);
+        assert_eq!(ini, Err(Error::NameDepth("a.b.c".into())));
+    }
+
+    #[test]
+    fn comment_entries() {
+        let text = r#"
+        ; first
+        a=1 ; inline
+        [foo]
+        # second
+        # third
+        "#;
+        let options = ParseOptions::new().comment_entries(true);
+        let ini = Parser::from_str_with(text, &options).unwrap();
+        let global: Vec<_> = ini[""].iter().collect();
+        assert_eq!(global, [("#1", "first"), ("a", "1")]);
+        let foo: Vec<_> = ini["foo"].iter().collect();
+        assert_eq!(foo, [("#1", "second"), ("#2", "third")]);
+    }
+
+    #[test]
+    fn comment_entries_reserved_name() {
+        let text = r##""#1"=foo"##;
+        let options = ParseOptions::new().comment_entries(true);
+        let ini = Parser::from_str_with(text, &options);
+        assert_eq!(ini, Err(Error::ReservedName("#1".into())));
+    }
+
     #[test]
     fn key_quoted_value() {
         let text = r#"foo="bar baz""#;
@@ -0,0 +1,63 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::Section;
+
+/// Characters that are percent-encoded by `Section::set_encoded`. Everything
+/// except ASCII alphanumerics and `_.-` is encoded.
+const ENCODED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'_').remove(b'.').remove(b'-');
+
+impl Section {
+    /// Get the value of a key with percent-encoded bytes decoded.
+    ///
+    /// For example, `a%20b` is decoded to `a b`. Returns `None` if the key does
+    /// not exist, if a `%` is not followed by two hexadecimal digits, or if the
+    /// decoded bytes are not valid UTF-8.
+    pub fn get_decoded(&self, name: &str) -> Option<String> {
+        let value = self.get_str(name)?;
+        let mut escapes = value.split('%').skip(1);
+        if !escapes
+            .all(|rest| rest.len() >= 2 && rest.as_bytes()[..2].iter().all(u8::is_ascii_hexdigit))
+        {
+            return None;
+        }
+        percent_decode_str(value).decode_utf8().ok().map(Into::into)
+    }
+
+    /// Insert a key with its value percent-encoded.
+    ///
+    /// Every character other than ASCII alphanumerics and `_.-` is encoded, so
+    /// the value contains no whitespace, quotes, or non-ASCII characters. If a
+    /// key exists with the same name, it is overwritten.
+    pub fn set_encoded(&mut self, name: &str, value: &str) {
+        self.insert(name.into(), utf8_percent_encode(value, ENCODED).to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ini;
+
+    #[test]
+    fn get_decoded() {
+        let ini = Ini::from_str("url=\"http://host/a%20b%2Fc\"").unwrap();
+        let decoded = ini[""].get_decoded("url");
+        assert_eq!(decoded.as_deref(), Some("http://host/a b/c"));
+    }
+
+    #[test]
+    fn get_decoded_malformed() {
+        let ini = Ini::from_str("a=\"100%\"\nb=\"%zz\"\nc=\"%ff\"").unwrap();
+        assert_eq!(ini[""].get_decoded("a"), None);
+        assert_eq!(ini[""].get_decoded("b"), None);
+        assert_eq!(ini[""].get_decoded("c"), None);
+    }
+
+    #[test]
+    fn set_encoded() {
+        let mut ini = Ini::new();
+        ini[""].set_encoded("path", "/a b/ü");
+        assert_eq!(ini.to_string(), "path=\"%2Fa%20b%2F%C3%BC\"\n");
+        let ini = Ini::from_str(&ini.to_string()).unwrap();
+        assert_eq!(ini[""].get_decoded("path").as_deref(), Some("/a b/ü"));
+    }
+}
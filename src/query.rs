@@ -0,0 +1,252 @@
+use std::collections::BTreeSet;
+
+use crate::{Ini, Section};
+
+impl Ini {
+    /// Collect the names of the keys defined in any section.
+    pub fn all_key_names(&self) -> BTreeSet<String> {
+        self.sections()
+            .flat_map(|(_, section)| section.iter())
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Iterate over the sections that define a key.
+    pub fn sections_with_key<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a Section)> {
+        self.sections()
+            .filter(move |(_, section)| section.contains_key(key))
+    }
+
+    /// Find the keys whose value is empty.
+    ///
+    /// Returns `(section, key)` pairs in config order.
+    pub fn find_empty_values(&self) -> Vec<(&str, &str)> {
+        self.find_value("")
+    }
+
+    /// Find the keys with the specified value.
+    ///
+    /// Returns `(section, key)` pairs in config order.
+    pub fn find_value(&self, value: &str) -> Vec<(&str, &str)> {
+        self.sections()
+            .flat_map(|(name, section)| {
+                section
+                    .iter()
+                    .filter(move |(_, v)| *v == value)
+                    .map(move |(key, _)| (name, key))
+            })
+            .collect()
+    }
+
+    /// Iterate over the sections nested directly under a section.
+    ///
+    /// Section names are split on `.`, so `[server.http]` is a child of
+    /// `server`, and `[server.http.tls]` a child of `server.http`. Children
+    /// are returned by their last name part, in config order. The parent
+    /// does not need to exist, and only children that exist are returned.
+    /// The children of "" are the sections whose names have no dots.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("[server.http]\nport=80\n[server.ssh]\nport=22").unwrap();
+    /// let children: Vec<_> = ini.subsections("server").map(|(name, _)| name).collect();
+    /// assert_eq!(children, ["http", "ssh"]);
+    /// ```
+    pub fn subsections<'a>(
+        &'a self,
+        parent: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a Section)> {
+        self.sections().filter_map(move |(name, section)| {
+            let child = match parent {
+                "" => name,
+                _ => name.strip_prefix(parent)?.strip_prefix('.')?,
+            };
+            let direct = !child.is_empty() && !child.contains('.');
+            direct.then_some((child, section))
+        })
+    }
+
+    /// Get the value of a key by its path of section name parts and key name.
+    ///
+    /// `["server", "http", "port"]` reads the key `port` in the section
+    /// `server.http`. A path with only a key name reads from the default
+    /// section. Returns `None` if the path is empty or the key does not
+    /// exist.
+    pub fn get_nested(&self, path: &[&str]) -> Option<&String> {
+        let (key, sections) = path.split_last()?;
+        self.section(&sections.join("."))?.get(key)
+    }
+
+    /// Get the value of a key by a path of the form `section.key`.
+    ///
+    /// The path is split on its first `.`, so `database.port` reads the key
+    /// `port` in the section `database`. A path without a section name, such
+    /// as `.port` or `port`, reads from the default section. Returns `None` if
+    /// the key does not exist.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("level=debug\n[database]\nport=5432").unwrap();
+    /// assert_eq!(ini.get_path("database.port").unwrap(), "5432");
+    /// assert_eq!(ini.get_path("level").unwrap(), "debug");
+    /// ```
+    pub fn get_path(&self, path: &str) -> Option<&String> {
+        let (section, key) = split_path(path);
+        self.section(section)?.get(key)
+    }
+
+    /// Set the value of a key by a path of the form `section.key`.
+    ///
+    /// The path is read as by `get_path`. The section is added if it does
+    /// not exist.
+    pub fn set_path(&mut self, path: &str, value: String) {
+        let (section, key) = split_path(path);
+        self.ensure_section(section).insert(key.into(), value);
+    }
+}
+
+/// Split a `section.key` path into its section and key names.
+fn split_path(path: &str) -> (&str, &str) {
+    path.split_once('.').unwrap_or(("", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_key_names() {
+        let ini = Ini::from_str(
+            r"
+            a=1
+            [foo]
+            b=2
+            a=3
+            [bar]
+            c=4
+            ",
+        )
+        .unwrap();
+        let names: Vec<_> = ini.all_key_names().into_iter().collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn find_empty_values() {
+        let ini = Ini::from_str(
+            r#"
+            a=""
+            b=1
+            [foo]
+            c=""
+            "#,
+        )
+        .unwrap();
+        assert_eq!(ini.find_empty_values(), [("", "a"), ("foo", "c")]);
+    }
+
+    #[test]
+    fn sections_with_key() {
+        let ini = Ini::from_str(
+            r"
+            [a]
+            type=disk
+            [b]
+            size=1
+            [c]
+            type=net
+            ",
+        )
+        .unwrap();
+        let names: Vec<_> = ini
+            .sections_with_key("type")
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["a", "c"]);
+    }
+
+    #[test]
+    fn find_value() {
+        let ini = Ini::from_str("a=true\nb=false\n[foo]\nc=true").unwrap();
+        assert_eq!(ini.find_value("true"), [("", "a"), ("foo", "c")]);
+        assert!(ini.find_value("maybe").is_empty());
+    }
+
+    #[test]
+    fn subsections() {
+        let ini = Ini::from_str(
+            r"
+            a=1
+            [server]
+            name=main
+            [server.http]
+            port=80
+            [serverless]
+            [server.http.tls]
+            cert=x
+            [server.ssh]
+            port=22
+            ",
+        )
+        .unwrap();
+        let names: Vec<_> = ini.subsections("server").map(|(name, _)| name).collect();
+        assert_eq!(names, ["http", "ssh"]);
+        let names: Vec<_> = ini
+            .subsections("server.http")
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["tls"]);
+        let names: Vec<_> = ini.subsections("").map(|(name, _)| name).collect();
+        assert_eq!(names, ["server", "serverless"]);
+        assert_eq!(ini.subsections("server.ssh").count(), 0);
+        assert_eq!(ini.subsections("missing").count(), 0);
+
+        let (_, http) = ini.subsections("server").next().unwrap();
+        assert_eq!(http["port"], "80");
+    }
+
+    #[test]
+    fn get_nested() {
+        let ini = Ini::from_str(
+            "a=1\n[server]\nname=main\n[server.http]\nport=80\n[server.http.tls]\ncert=x",
+        )
+        .unwrap();
+        assert_eq!(ini.get_nested(&["a"]).unwrap(), "1");
+        assert_eq!(ini.get_nested(&["server", "name"]).unwrap(), "main");
+        assert_eq!(ini.get_nested(&["server", "http", "port"]).unwrap(), "80");
+        assert_eq!(
+            ini.get_nested(&["server", "http", "tls", "cert"]).unwrap(),
+            "x"
+        );
+        assert_eq!(ini.get_nested(&["server", "port"]), None);
+        assert_eq!(ini.get_nested(&["missing", "port"]), None);
+        assert_eq!(ini.get_nested(&[]), None);
+    }
+
+    #[test]
+    fn get_path() {
+        let ini = Ini::from_str("a=1\n[database]\nport=5432\nhost.name=db").unwrap();
+        assert_eq!(ini.get_path("database.port").unwrap(), "5432");
+        assert_eq!(ini.get_path("database.host.name").unwrap(), "db");
+        assert_eq!(ini.get_path("a").unwrap(), "1");
+        assert_eq!(ini.get_path(".a").unwrap(), "1");
+        assert_eq!(ini.get_path("database.user"), None);
+        assert_eq!(ini.get_path("missing.port"), None);
+        assert_eq!(ini.get_path("port"), None);
+    }
+
+    #[test]
+    fn set_path() {
+        let mut ini = Ini::new();
+        ini.set_path("database.port", "5432".into());
+        ini.set_path("level", "debug".into());
+        ini.set_path(".level", "info".into());
+        ini.set_path("database.port", "5433".into());
+        assert_eq!(ini.to_string(), "level=info\n\n[database]\nport=5433\n");
+    }
+}
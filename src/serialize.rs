@@ -0,0 +1,120 @@
+use std::fmt::{self, Formatter};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Ini, Section};
+
+impl Serialize for Section {
+    /// Serialize the section as a map from key names to values, in order.
+    ///
+    /// Only the last value of each key is included, and comments are not.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Section {
+    /// Deserialize a section from a map from key names to string values.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SectionVisitor)
+    }
+}
+
+struct SectionVisitor;
+
+impl<'de> Visitor<'de> for SectionVisitor {
+    type Value = Section;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("a map of key names to string values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Section, A::Error> {
+        let mut section = Section::new();
+        while let Some((key, value)) = map.next_entry()? {
+            section.insert(key, value);
+        }
+        Ok(section)
+    }
+}
+
+impl Serialize for Ini {
+    /// Serialize the config as a map from section names to sections, in
+    /// order.
+    ///
+    /// The default section is included under the name "". With `serde_json`,
+    /// `a=1` followed by `[foo]` and `b=2` becomes
+    /// `{"":{"a":"1"},"foo":{"b":"2"}}`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (name, section) in self.sections() {
+            map.serialize_entry(name, section)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Ini {
+    /// Deserialize a config from a map from section names to sections, as
+    /// written by `Serialize`.
+    ///
+    /// A section named "" is read into the default section. The default
+    /// section exists even if the map does not include it.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(IniVisitor)
+    }
+}
+
+struct IniVisitor;
+
+impl<'de> Visitor<'de> for IniVisitor {
+    type Value = Ini;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("a map of section names to sections")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Ini, A::Error> {
+        let mut ini = Ini::new();
+        while let Some((name, section)) = map.next_entry::<String, Section>()? {
+            ini.replace_section(&name, section);
+        }
+        Ok(ini)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let ini = Ini::from_str("a=1\n[foo]\nb=\"two words\"\nc=3\n[bar]").unwrap();
+        let json = serde_json::to_string(&ini).unwrap();
+        assert_eq!(
+            json,
+            r#"{"":{"a":"1"},"foo":{"b":"two words","c":"3"},"bar":{}}"#
+        );
+        let parsed: Ini = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, ini);
+        assert_eq!(parsed.to_string(), ini.to_string());
+    }
+
+    #[test]
+    fn json_without_default_section() {
+        let ini: Ini = serde_json::from_str(r#"{"foo":{"b":"2"}}"#).unwrap();
+        assert!(ini.contains_section(""));
+        assert_eq!(ini.to_string(), "[foo]\nb=2\n");
+
+        assert!(serde_json::from_str::<Ini>(r#"{"foo":{"b":2}}"#).is_err());
+        assert!(serde_json::from_str::<Ini>(r#"["foo"]"#).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Ini, Section};
+
+/// Saved state of a section, created by `Section::snapshot`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSnapshot(Section);
+
+/// Saved state of a config, created by `Ini::snapshot`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IniSnapshot(Ini);
+
+impl Section {
+    /// Save the current keys and comments of the section.
+    ///
+    /// Pass the snapshot to `restore` to undo any edits made since.
+    pub fn snapshot(&self) -> SectionSnapshot {
+        SectionSnapshot(self.clone())
+    }
+
+    /// Return the section to the state saved in a snapshot.
+    pub fn restore(&mut self, snapshot: SectionSnapshot) {
+        *self = snapshot.0;
+    }
+}
+
+impl Ini {
+    /// Save the current sections, keys, and comments of the config.
+    ///
+    /// Pass the snapshot to `restore` to undo any edits made since.
+    pub fn snapshot(&self) -> IniSnapshot {
+        IniSnapshot(self.clone())
+    }
+
+    /// Return the config to the state saved in a snapshot.
+    pub fn restore(&mut self, snapshot: IniSnapshot) {
+        *self = snapshot.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_restore() {
+        let mut ini = Ini::from_str("a=1").unwrap();
+        let snapshot = ini[""].snapshot();
+        ini[""]["a"] = "2".into();
+        ini[""].insert("b".into(), "3".into());
+        ini[""].restore(snapshot);
+        assert_eq!(ini, Ini::from_str("a=1").unwrap());
+    }
+
+    #[test]
+    fn ini_restore() {
+        let mut ini = Ini::from_str("[foo]\na=1").unwrap();
+        let snapshot = ini.snapshot();
+        ini.add_section("bar");
+        ini["foo"]["a"] = "2".into();
+        ini.restore(snapshot);
+        assert_eq!(ini, Ini::from_str("[foo]\na=1").unwrap());
+    }
+}
@@ -0,0 +1,601 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    error::{Error, Result},
+    ini::Section,
+    Ini,
+};
+
+/// Types that can be built from the keys of a section.
+///
+/// Implementations typically check for required keys with
+/// [`Section::require`] and then read each field with the typed accessors.
+///
+/// ```
+/// use ini::{FromSection, Ini, Result, Section};
+///
+/// struct Server {
+///     host: String,
+///     port: i64,
+///     verbose: bool,
+/// }
+///
+/// impl FromSection for Server {
+///     fn from_section(section: &Section) -> Result<Self> {
+///         section.require(&["host", "port"])?;
+///         Ok(Server {
+///             host: section["host"].clone(),
+///             port: section.get_int("port")?,
+///             verbose: section.get_bool("verbose").unwrap_or(false),
+///         })
+///     }
+/// }
+///
+/// let ini = Ini::from_str("
+///     [server]
+///     host=localhost
+///     port=8080
+/// ").unwrap();
+///
+/// let server: Server = ini["server"].into_struct().unwrap();
+/// assert_eq!(server.host, "localhost");
+/// assert_eq!(server.port, 8080);
+/// assert!(!server.verbose);
+/// ```
+pub trait FromSection: Sized {
+    /// Build a value from a section.
+    fn from_section(section: &Section) -> Result<Self>;
+}
+
+/// Types that can be built from a section of flags.
+///
+/// A flag is set if its key is present with an empty value, as written by a
+/// bare key such as `fast` (see `ParseOptions::bare_keys`), or if its value
+/// reads as `true` with `Section::get_bool`.
+///
+/// ```
+/// use ini::{FromFlagSection, Ini, ParseOptions, Result, Section};
+///
+/// struct Features {
+///     fast: bool,
+///     safe: bool,
+/// }
+///
+/// impl FromFlagSection for Features {
+///     const FLAGS: &'static [&'static str] = &["fast", "safe"];
+///
+///     fn from_flags(section: &Section) -> Result<Self> {
+///         Ok(Features {
+///             fast: section.flag("fast")?,
+///             safe: section.flag("safe")?,
+///         })
+///     }
+/// }
+///
+/// let options = ParseOptions::new().bare_keys(true);
+/// let ini = Ini::from_str_with("
+///     [features]
+///     fast
+///     turbo
+/// ", &options).unwrap();
+///
+/// let (features, unknown) = ini["features"].into_flags::<Features>().unwrap();
+/// assert!(features.fast);
+/// assert!(!features.safe);
+/// assert_eq!(unknown, ["turbo"]);
+/// ```
+pub trait FromFlagSection: Sized {
+    /// The names of the flags that the type reads.
+    const FLAGS: &'static [&'static str];
+
+    /// Build a value from a section.
+    fn from_flags(section: &Section) -> Result<Self>;
+}
+
+/// Types that can be built from a whole config.
+///
+/// `SCHEMA` lists every section the type reads, with the keys it reads in
+/// each. `Ini::load_strict` uses it to reject sections and keys that the type
+/// would ignore.
+///
+/// ```
+/// use ini::{FromIni, Ini, Result};
+///
+/// struct Config {
+///     host: String,
+///     port: i64,
+/// }
+///
+/// impl FromIni for Config {
+///     const SCHEMA: &'static [(&'static str, &'static [&'static str])] =
+///         &[("server", &["host", "port"])];
+///
+///     fn from_ini(ini: &Ini) -> Result<Self> {
+///         let server = &ini["server"];
+///         server.require(&["host", "port"])?;
+///         Ok(Config {
+///             host: server["host"].clone(),
+///             port: server.get_int("port")?,
+///         })
+///     }
+/// }
+///
+/// let config: Config = Ini::load_strict("
+///     [server]
+///     host=localhost
+///     port=8080
+/// ").unwrap();
+/// assert_eq!(config.port, 8080);
+///
+/// let config = Ini::load_strict::<Config>("
+///     [server]
+///     host=localhost
+///     port=8080
+///     debug=true
+/// ");
+/// assert!(config.is_err());
+/// ```
+pub trait FromIni: Sized {
+    /// The sections the type reads, each with the keys it reads.
+    const SCHEMA: &'static [(&'static str, &'static [&'static str])];
+
+    /// Build a value from a config.
+    fn from_ini(ini: &Ini) -> Result<Self>;
+}
+
+impl Ini {
+    /// Parse a config and build a value from it, rejecting anything the
+    /// value does not read.
+    ///
+    /// Every section and key in the text must be listed in `T::SCHEMA`. The
+    /// default section may be empty without being listed. Each unlisted
+    /// section is reported as `Error::UnknownSection`, and each unlisted key
+    /// in a listed section as `Error::UnknownKey`. These are reported along
+    /// with any error from `T::from_ini`, such as missing keys or a value of
+    /// the wrong type. If there is more than one issue, they are returned
+    /// together as `Error::Multiple`.
+    pub fn load_strict<T: FromIni>(text: &str) -> Result<T> {
+        let ini = Ini::from_str(text)?;
+
+        let mut errors = Vec::new();
+        for (name, section) in ini.sections() {
+            let known = T::SCHEMA.iter().find(|(known, _)| *known == name);
+            match known {
+                Some((_, keys)) => {
+                    for (key, _) in section.iter().filter(|(key, _)| !keys.contains(key)) {
+                        errors.push(Error::UnknownKey {
+                            section: name.into(),
+                            key: key.into(),
+                        });
+                    }
+                }
+                None if name.is_empty() && section.iter().next().is_none() => {}
+                None => errors.push(Error::UnknownSection(name.into())),
+            }
+        }
+
+        let value = T::from_ini(&ini);
+        match value {
+            Ok(value) if errors.is_empty() => return Ok(value),
+            Ok(_) => {}
+            Err(Error::Multiple(inner)) => errors.extend(inner),
+            Err(error) => errors.push(error),
+        }
+
+        if errors.len() == 1 {
+            Err(errors.remove(0))
+        } else {
+            Err(Error::Multiple(errors))
+        }
+    }
+}
+
+impl Section {
+    /// Build a value from the keys of this section.
+    pub fn into_struct<T: FromSection>(&self) -> Result<T> {
+        T::from_section(self)
+    }
+
+    /// Build a value from a section of flags.
+    ///
+    /// Returns the value along with the names of any keys in the section
+    /// that are not listed in `T::FLAGS`, so that callers can warn about
+    /// them.
+    pub fn into_flags<T: FromFlagSection>(&self) -> Result<(T, Vec<String>)> {
+        let value = T::from_flags(self)?;
+        let unknown = self
+            .iter()
+            .filter(|(name, _)| !T::FLAGS.contains(name))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        Ok((value, unknown))
+    }
+
+    /// Check whether a flag is set.
+    ///
+    /// Returns `false` if the key does not exist and `true` if its value is
+    /// empty. Otherwise the value is read with `get_bool`.
+    pub fn flag(&self, name: &str) -> Result<bool> {
+        match self.get_str(name) {
+            None => Ok(false),
+            Some("") => Ok(true),
+            Some(_) => self.get_bool(name),
+        }
+    }
+
+    /// Check that all the specified keys exist.
+    ///
+    /// Returns `Error::MissingKeys` listing every missing key, in the order
+    /// they were specified.
+    pub fn require(&self, names: &[&str]) -> Result<()> {
+        let missing: Vec<String> = names
+            .iter()
+            .filter(|name| self.get_str(name).is_none())
+            .map(|name| name.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingKeys(missing))
+        }
+    }
+
+    /// Get the value of a key as a boolean.
+    ///
+    /// The values `true`, `yes`, `on`, and `1` are read as `true`, and `false`,
+    /// `no`, `off`, and `0` are read as `false`, ignoring case. Returns
+    /// `Error::MissingKey` if the key does not exist and `Error::Type` if the
+    /// value is not one of these.
+    pub fn get_bool(&self, name: &str) -> Result<bool> {
+        let value = self.typed_value(name)?;
+        parse_bool(value).ok_or_else(|| Error::Type(name.into()))
+    }
+
+    /// Get the value of a key as an integer.
+    ///
+    /// The value may have a leading sign, a `0x`, `0o`, or `0b` prefix for
+    /// hexadecimal, octal, or binary digits, and underscores between digits
+    /// (for example `1_000`). Returns `Error::MissingKey` if the key does not
+    /// exist and `Error::Type` if the value is not an integer.
+    pub fn get_int(&self, name: &str) -> Result<i64> {
+        let value = self.typed_value(name)?;
+        parse_int(value).ok_or_else(|| Error::Type(name.into()))
+    }
+
+    /// Get the value of a key as a floating-point number.
+    ///
+    /// The value is parsed with `f64::from_str`, so forms like `3.14`,
+    /// `-1e-9`, and `inf` are accepted. Returns `Error::MissingKey` if the key
+    /// does not exist and `Error::Type` if the value is not a number.
+    pub fn get_float(&self, name: &str) -> Result<f64> {
+        let value = self.typed_value(name)?;
+        value.parse().map_err(|_| Error::Type(name.into()))
+    }
+
+    /// Get the value of a key parsed with `FromStr`.
+    ///
+    /// This reads any type that implements `FromStr`, such as `u16`,
+    /// `IpAddr`, or `PathBuf`. Returns `Error::MissingKey` if the key does
+    /// not exist and `Error::Type` if the value fails to parse.
+    ///
+    /// ```
+    /// use std::net::IpAddr;
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("[server]\nhost=127.0.0.1\nport=8080").unwrap();
+    /// let host: IpAddr = ini["server"].get_parsed("host").unwrap();
+    /// assert!(host.is_loopback());
+    /// assert_eq!(ini["server"].get_parsed::<u16>("port"), Ok(8080));
+    /// ```
+    pub fn get_parsed<T: FromStr>(&self, name: &str) -> Result<T> {
+        let value = self.typed_value(name)?;
+        value.parse().map_err(|_| Error::Type(name.into()))
+    }
+
+    /// Get the value of a key split into exactly `N` fields.
+    ///
+    /// The value is split on every occurrence of `sep`, and whitespace is
+    /// trimmed from each field. Empty fields are kept, so `10-` split on `-`
+    /// gives `["10", ""]`. Returns `None` if the key does not exist or the
+    /// number of fields is not `N`.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("range=10-20").unwrap();
+    /// assert_eq!(ini[""].get_split_n("range", "-"), Some(["10".into(), "20".into()]));
+    /// assert_eq!(ini[""].get_split_n::<3>("range", "-"), None);
+    /// ```
+    pub fn get_split_n<const N: usize>(&self, name: &str, sep: &str) -> Option<[String; N]> {
+        let value = self.get_str(name)?;
+        let fields: Vec<String> = value.split(sep).map(|field| field.trim().into()).collect();
+        fields.try_into().ok()
+    }
+
+    /// Get the value of a key as a `major.minor.patch` version.
+    ///
+    /// Each part must be a decimal number. Versions with pre-release or build
+    /// suffixes, such as `1.2.3-beta` or `1.2.3+abc`, are rejected rather than
+    /// truncated, since they do not compare equal to the plain version.
+    /// Returns `None` if the key does not exist or the value is not a
+    /// version.
+    pub fn get_version(&self, name: &str) -> Option<(u64, u64, u64)> {
+        let value = self.get_str(name)?;
+        let parts: Vec<u64> = value
+            .split('.')
+            .map(|part| {
+                if part.bytes().all(|b| b.is_ascii_digit()) {
+                    part.parse().ok()
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<_>>()?;
+        match parts[..] {
+            [major, minor, patch] => Some((major, minor, patch)),
+            _ => None,
+        }
+    }
+
+    /// Parse every value in the section into `T`.
+    ///
+    /// This fails fast: the first value that does not parse, in declaration
+    /// order, fails with `Error::Type` naming its key. Use
+    /// [`Section::to_typed_map_lossy`] to skip such values instead.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("[limits]\ncpu=4\nmemory=512").unwrap();
+    /// let limits = ini["limits"].to_typed_map::<u32>().unwrap();
+    /// assert_eq!(limits["memory"], 512);
+    /// ```
+    pub fn to_typed_map<T: FromStr>(&self) -> Result<HashMap<String, T>> {
+        self.iter()
+            .map(|(name, value)| match value.parse() {
+                Ok(value) => Ok((name.into(), value)),
+                Err(_) => Err(Error::Type(name.into())),
+            })
+            .collect()
+    }
+
+    /// Parse every value in the section into `T`, skipping values that do not
+    /// parse.
+    pub fn to_typed_map_lossy<T: FromStr>(&self) -> HashMap<String, T> {
+        self.iter()
+            .filter_map(|(name, value)| Some((name.into(), value.parse().ok()?)))
+            .collect()
+    }
+
+    /// Get the value of a key for conversion to another type.
+    fn typed_value(&self, name: &str) -> Result<&str> {
+        self.get_str(name)
+            .ok_or_else(|| Error::MissingKey(name.into()))
+    }
+}
+
+/// Parse a boolean as read by `Section::get_bool`.
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse an integer with an optional sign, radix prefix, and digit
+/// separators.
+pub(crate) fn parse_int(value: &str) -> Option<i64> {
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'-' => ("-", &value[1..]),
+        b'+' => ("", &value[1..]),
+        _ => ("", value),
+    };
+
+    let prefix = rest.get(..2).map(str::to_ascii_lowercase);
+    let (radix, digits) = match prefix.as_deref() {
+        Some("0x") => (16, &rest[2..]),
+        Some("0o") => (8, &rest[2..]),
+        Some("0b") => (2, &rest[2..]),
+        _ => (10, rest),
+    };
+
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') {
+        return None;
+    }
+    if digits.starts_with(['+', '-']) || digits.contains("__") {
+        return None;
+    }
+
+    let digits = digits.replace('_', "");
+    i64::from_str_radix(&format!("{sign}{digits}"), radix).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv6Addr};
+
+    use super::*;
+    use crate::Ini;
+
+    struct Server {
+        host: String,
+        port: i64,
+    }
+
+    impl FromSection for Server {
+        fn from_section(section: &Section) -> Result<Self> {
+            section.require(&["host", "port"])?;
+            Ok(Server {
+                host: section["host"].clone(),
+                port: section.get_int("port")?,
+            })
+        }
+    }
+
+    #[test]
+    fn into_struct() {
+        let ini = Ini::from_str("host=localhost\nport=0x1F90").unwrap();
+        let server: Server = ini[""].into_struct().unwrap();
+        assert_eq!(server.host, "localhost");
+        assert_eq!(server.port, 8080);
+    }
+
+    #[test]
+    fn into_struct_missing_fields() {
+        let ini = Ini::new();
+        let server = ini[""].into_struct::<Server>();
+        let missing = vec!["host".to_string(), "port".to_string()];
+        assert!(matches!(server, Err(Error::MissingKeys(keys)) if keys == missing));
+    }
+
+    #[test]
+    fn into_struct_type_error() {
+        let ini = Ini::from_str("host=localhost\nport=http").unwrap();
+        let server = ini[""].into_struct::<Server>();
+        assert!(matches!(server, Err(Error::Type(key)) if key == "port"));
+    }
+
+    #[test]
+    fn get_bool() {
+        let text = "a=true\nb=YES\nc=On\nd=1\ne=False\nf=no\ng=OFF\nh=0\ni=maybe";
+        let ini = Ini::from_str(text).unwrap();
+        for name in ["a", "b", "c", "d"] {
+            assert_eq!(ini[""].get_bool(name), Ok(true));
+        }
+        for name in ["e", "f", "g", "h"] {
+            assert_eq!(ini[""].get_bool(name), Ok(false));
+        }
+        assert_eq!(ini[""].get_bool("i"), Err(Error::Type("i".into())));
+        assert_eq!(ini[""].get_bool("j"), Err(Error::MissingKey("j".into())));
+    }
+
+    #[test]
+    fn get_int() {
+        let text = "a=42\nb=-1_000\nc=\"+7\"\nd=0xff\ne=0O17\nf=-0b101\ng=1__0\nh=_1\ni=0x\nj=1.5";
+        let ini = Ini::from_str(text).unwrap();
+        assert_eq!(ini[""].get_int("a"), Ok(42));
+        assert_eq!(ini[""].get_int("b"), Ok(-1000));
+        assert_eq!(ini[""].get_int("c"), Ok(7));
+        assert_eq!(ini[""].get_int("d"), Ok(255));
+        assert_eq!(ini[""].get_int("e"), Ok(15));
+        assert_eq!(ini[""].get_int("f"), Ok(-5));
+        for name in ["g", "h", "i", "j"] {
+            assert_eq!(ini[""].get_int(name), Err(Error::Type(name.into())));
+        }
+        assert_eq!(ini[""].get_int("k"), Err(Error::MissingKey("k".into())));
+    }
+
+    #[test]
+    fn get_float() {
+        let ini = Ini::from_str("a=2.5\nb=-1e-9\nc=inf\nd=1.2.3").unwrap();
+        assert_eq!(ini[""].get_float("a"), Ok(2.5));
+        assert_eq!(ini[""].get_float("b"), Ok(-1e-9));
+        assert_eq!(ini[""].get_float("c"), Ok(f64::INFINITY));
+        assert_eq!(ini[""].get_float("d"), Err(Error::Type("d".into())));
+        assert_eq!(ini[""].get_float("e"), Err(Error::MissingKey("e".into())));
+    }
+
+    #[test]
+    fn get_parsed() {
+        let ini = Ini::from_str("port=8080\nbig=70000\nhost=\"::1\"\nbad=1.2.3.4.5").unwrap();
+        assert_eq!(ini[""].get_parsed::<u16>("port"), Ok(8080));
+        assert_eq!(
+            ini[""].get_parsed::<u16>("big"),
+            Err(Error::Type("big".into()))
+        );
+        assert_eq!(
+            ini[""].get_parsed::<IpAddr>("host"),
+            Ok(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            ini[""].get_parsed::<IpAddr>("bad"),
+            Err(Error::Type("bad".into()))
+        );
+        assert_eq!(
+            ini[""].get_parsed::<u16>("missing"),
+            Err(Error::MissingKey("missing".into()))
+        );
+    }
+
+    #[test]
+    fn get_split_n() {
+        let ini = Ini::from_str("range=\"10 - 20\"\nopen=10-").unwrap();
+        let range = ini[""].get_split_n("range", "-");
+        assert_eq!(range, Some(["10".to_string(), "20".to_string()]));
+        let open = ini[""].get_split_n("open", "-");
+        assert_eq!(open, Some(["10".to_string(), "".to_string()]));
+        assert_eq!(ini[""].get_split_n::<3>("range", "-"), None);
+        assert_eq!(ini[""].get_split_n::<2>("missing", "-"), None);
+    }
+
+    #[test]
+    fn flag() {
+        let ini = Ini::from_str("a=\"\"\nb=yes\nc=off\nd=maybe").unwrap();
+        assert_eq!(ini[""].flag("a"), Ok(true));
+        assert_eq!(ini[""].flag("b"), Ok(true));
+        assert_eq!(ini[""].flag("c"), Ok(false));
+        assert_eq!(ini[""].flag("d"), Err(Error::Type("d".into())));
+        assert_eq!(ini[""].flag("e"), Ok(false));
+    }
+
+    #[test]
+    fn get_version() {
+        let ini = Ini::from_str("a=1.2.3\nb=1.2\nc=1.2.3-beta\nd=\"1.+2.3\"").unwrap();
+        assert_eq!(ini[""].get_version("a"), Some((1, 2, 3)));
+        assert_eq!(ini[""].get_version("b"), None);
+        assert_eq!(ini[""].get_version("c"), None);
+        assert_eq!(ini[""].get_version("d"), None);
+    }
+
+    struct Config {
+        server: Server,
+    }
+
+    impl FromIni for Config {
+        const SCHEMA: &'static [(&'static str, &'static [&'static str])] =
+            &[("server", &["host", "port"])];
+
+        fn from_ini(ini: &Ini) -> Result<Self> {
+            let section = ini.section("server").cloned().unwrap_or_default();
+            Ok(Config {
+                server: section.into_struct()?,
+            })
+        }
+    }
+
+    #[test]
+    fn load_strict() {
+        let config: Config = Ini::load_strict("[server]\nhost=a\nport=1").unwrap();
+        assert_eq!(config.server.host, "a");
+        assert_eq!(config.server.port, 1);
+    }
+
+    #[test]
+    fn to_typed_map() {
+        let ini = Ini::from_str("[ports]\nhttp=80\nhttps=443\nadmin=none").unwrap();
+        let ports = ini["ports"].to_typed_map::<u16>();
+        assert_eq!(ports, Err(Error::Type("admin".into())));
+        let ports = ini["ports"].to_typed_map_lossy::<u16>();
+        assert_eq!(
+            ports,
+            HashMap::from([("http".into(), 80), ("https".into(), 443)])
+        );
+    }
+
+    #[test]
+    fn load_strict_aggregates_errors() {
+        let text = "extra=1\n[server]\nhost=a\nmode=fast\n[cache]";
+        let config = Ini::load_strict::<Config>(text);
+        let expected = Error::Multiple(vec![
+            Error::UnknownSection("".into()),
+            Error::UnknownKey {
+                section: "server".into(),
+                key: "mode".into(),
+            },
+            Error::UnknownSection("cache".into()),
+            Error::MissingKeys(vec!["port".into()]),
+        ]);
+        assert!(matches!(config, Err(error) if error == expected));
+    }
+}
@@ -0,0 +1,433 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::{escape::escape_value, ini::Section, Ini, WriteOptions};
+
+impl Ini {
+    /// Serialize the config to INI text using the specified options.
+    ///
+    /// Keys in the default section are written first, without a section
+    /// header, since any key that follows a header belongs to that section.
+    /// Each remaining section follows under its own header. Sections and keys
+    /// are written in insertion order, or sorted by name if
+    /// `WriteOptions::sorted` is set. Names and values that cannot be written
+    /// as bare strings are quoted. A key with several values is written once
+    /// per value, with `[]` after its name on every line but the first.
+    /// Comments read by the parser are written back in place. Lines end as
+    /// set by `WriteOptions::newline`.
+    pub fn write_to<W: Write>(&self, out: &mut W, options: &WriteOptions) -> fmt::Result {
+        let out = &mut LineEndings {
+            out,
+            newline: options.newline.as_str(),
+        };
+        let mut first = true;
+        if let Some(section) = self.section("") {
+            if section.iter().next().is_some() || !section.header_comments.is_empty() {
+                match &options.global_section_header {
+                    Some(header) => write_header(out, header, section)?,
+                    None => write_comments(out, &section.header_comments)?,
+                }
+                write_section(out, section, options)?;
+                first = false;
+            }
+        }
+
+        let mut sections: Vec<_> = self
+            .sections()
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        if options.sorted {
+            sections.sort_by_key(|(name, _)| *name);
+        }
+        for (name, section) in sections {
+            if !first {
+                out.write_char('\n')?;
+            }
+            write_header(out, name, section)?;
+            write_section(out, section, options)?;
+            first = false;
+        }
+
+        write_comments(out, &self.comments)
+    }
+
+    /// Serialize the config to a string using the specified options.
+    pub fn to_string_with(&self, options: &WriteOptions) -> String {
+        let mut out = String::new();
+        self.write_to(&mut out, options)
+            .expect("writing to a string should not fail");
+        out
+    }
+
+    /// Serialize only the sections and keys that differ from a set of
+    /// defaults.
+    ///
+    /// A key is written if it is missing from the same section in `defaults`
+    /// or has different values there. Keys with several values are compared
+    /// value by value and written with all of their values. A section is
+    /// written if it has such keys or is missing from `defaults` entirely.
+    /// Keys that exist in `defaults` but not in this config are not written,
+    /// since leaving a key out of an override file means the default applies.
+    pub fn to_string_diff(&self, defaults: &Ini) -> String {
+        let mut diff = self.empty_config();
+        for (name, section) in self.sections() {
+            let base = defaults.section(name);
+            let mut changed = self.empty_section();
+            for key in section.keys() {
+                let values = section.get_all(key);
+                if base.is_some_and(|base| base.get_all(key) == values) {
+                    continue;
+                }
+                for value in values {
+                    changed.append_shared(key.into(), value.clone());
+                }
+                if let Some(comment) = section.inline_comment(key) {
+                    changed.set_inline_comment(key, comment.into());
+                }
+            }
+            if base.is_none() || changed.iter().next().is_some() {
+                diff.replace_section(name, changed);
+            }
+        }
+        diff.to_string()
+    }
+}
+
+/// Maximum number of characters of a value shown by `Ini::to_tree_string`.
+const TREE_VALUE_WIDTH: usize = 40;
+
+impl Ini {
+    /// Render the config as an indented tree for display.
+    ///
+    /// Each section is shown as a header line, with its keys indented below
+    /// it as `key = value`. The default section is shown as `(global)` if it
+    /// has keys. Values longer than 40 characters are truncated and end in
+    /// `...`. The output is meant for people to read and is not valid INI
+    /// text; use `to_string` to serialize the config.
+    ///
+    /// ```
+    /// use ini::Ini;
+    ///
+    /// let ini = Ini::from_str("
+    ///     level=debug
+    ///     [db]
+    ///     host=localhost
+    /// ").unwrap();
+    ///
+    /// assert_eq!(ini.to_tree_string(), "\
+    /// (global)
+    ///   level = debug
+    /// [db]
+    ///   host = localhost
+    /// ");
+    /// ```
+    pub fn to_tree_string(&self) -> String {
+        let mut out = String::new();
+        for (name, section) in self.sections() {
+            if name.is_empty() {
+                if section.iter().next().is_none() {
+                    continue;
+                }
+                out.push_str("(global)\n");
+            } else {
+                out.push_str(&format!("[{name}]\n"));
+            }
+            for (key, value) in section.iter() {
+                out.push_str(&format!("  {key} = {}\n", truncate(value)));
+            }
+        }
+        out
+    }
+}
+
+/// Writer that replaces each `\n` with another line ending.
+struct LineEndings<'a, W> {
+    out: &'a mut W,
+    newline: &'static str,
+}
+
+impl<W: Write> Write for LineEndings<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.newline == "\n" {
+            return self.out.write_str(s);
+        }
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.out.write_str(first)?;
+        }
+        for line in lines {
+            self.out.write_str(self.newline)?;
+            self.out.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shorten a value for display.
+fn truncate(value: &str) -> String {
+    if value.chars().count() <= TREE_VALUE_WIDTH {
+        return value.into();
+    }
+    let start: String = value.chars().take(TREE_VALUE_WIDTH - 3).collect();
+    format!("{start}...")
+}
+
+impl Display for Ini {
+    /// Serialize the config to INI text using the default options.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &WriteOptions::default())
+    }
+}
+
+fn write_header<W: Write>(out: &mut W, name: &str, section: &Section) -> fmt::Result {
+    write_comments(out, &section.header_comments)?;
+    write!(out, "[{}]", escape_value(name))?;
+    if let Some(comment) = &section.header_comment {
+        write!(out, " {comment}")?;
+    }
+    out.write_char('\n')
+}
+
+fn write_comments<W: Write>(out: &mut W, comments: &[String]) -> fmt::Result {
+    for comment in comments {
+        writeln!(out, "{comment}")?;
+    }
+    Ok(())
+}
+
+/// Write the keys of a section, one per line.
+fn write_section<W: Write>(out: &mut W, section: &Section, options: &WriteOptions) -> fmt::Result {
+    let delimiter = if options.spaced_delimiter { " = " } else { "=" };
+    let mut keys: Vec<_> = section.iter().collect();
+    if options.sorted {
+        keys.sort_by_key(|(name, _)| *name);
+    }
+    for (name, value) in keys {
+        write_comments(out, section.leading_comments(name))?;
+        // Values after the first are written as list appends, `key[]=value`,
+        // so that every value is read back whatever the duplicate key policy.
+        let earlier = section.earlier(name);
+        for (ix, earlier) in earlier.iter().enumerate() {
+            let list = if ix == 0 { "" } else { "[]" };
+            writeln!(
+                out,
+                "{}{list}{delimiter}{}",
+                escape_value(name),
+                escape_value(earlier)
+            )?;
+        }
+        let list = if earlier.is_empty() { "" } else { "[]" };
+        write!(
+            out,
+            "{}{list}{delimiter}{}",
+            escape_value(name),
+            escape_value(value)
+        )?;
+        if let Some(comment) = section.inline_comment(name) {
+            write!(out, " {comment}")?;
+        }
+        out.write_char('\n')?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Newline, ParseOptions};
+
+    #[test]
+    fn display() {
+        let ini = Ini::from_str(
+            r#"
+            a=1
+            [foo]
+            b="two words"
+            [bar]
+            "#,
+        )
+        .unwrap();
+        let text = ini.to_string();
+        assert_eq!(text, "a=1\n\n[foo]\nb=\"two words\"\n\n[bar]\n");
+        assert_eq!(Ini::from_str(&text), Ok(ini));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let mut ini = Ini::new();
+        ini.add_section("a b");
+        let values = [
+            "",
+            "x=y",
+            "say \"hi\"",
+            "a;b",
+            "#c",
+            "[d]",
+            "tab\there",
+            "two\nlines",
+        ];
+        for (ix, value) in values.into_iter().enumerate() {
+            ini["a b"].insert(format!("key {ix}"), value.into());
+        }
+        assert_eq!(Ini::from_str(&ini.to_string()), Ok(ini));
+    }
+
+    #[test]
+    fn display_order() {
+        let text = "b=2\na=1\n\n[z]\ny=1\nx=2\n\n[a]\nk=v\n";
+        let ini = Ini::from_str(text).unwrap();
+        assert_eq!(ini.to_string(), text);
+    }
+
+    #[test]
+    fn display_comments() {
+        let text =
+            "; leading\na=1 ; inline\n\n# about foo\n[foo] ; header\n; before b\nb=2\n; trailing\n";
+        let ini = Ini::from_str(text).unwrap();
+        assert_eq!(ini.to_string(), text);
+
+        let text = "; only comments\n[foo]\n";
+        assert_eq!(Ini::from_str(text).unwrap().to_string(), text);
+
+        let mut ini = Ini::from_str(text).unwrap();
+        ini["foo"].insert("a".into(), "1".into());
+        ini["foo"].remove("a");
+        assert_eq!(ini.to_string(), text);
+    }
+
+    #[test]
+    fn display_comment_prefix() {
+        let text = "a=1 # note
+
+[foo] # header
+b=\"x;y\" # other
+";
+        let ini = Ini::from_str(text).unwrap();
+        assert_eq!(ini[""].comment_for("a"), Some("note"));
+        assert_eq!(ini.to_string(), text);
+
+        let options = ParseOptions::new().comment_prefixes(&['#']);
+        let mut ini = Ini::from_str_with(text, &options).unwrap();
+        ini[""].set_comment("a", "changed").unwrap();
+        let text = ini.to_string();
+        assert_eq!(text, "a=1 # changed\n\n[foo] # header\nb=\"x;y\" # other\n");
+        assert_eq!(Ini::from_str_with(&text, &options), Ok(ini));
+    }
+
+    #[test]
+    fn display_order_after_edits() {
+        let mut ini = Ini::from_str("[z]\ny=1\nx=2\n\n[a]\nk=v\n").unwrap();
+        ini.add_section("z");
+        ini["z"].insert("w".into(), "3".into());
+        ini["a"].insert("k".into(), "u".into());
+        ini.add_section("m");
+        assert_eq!(ini.to_string(), "[z]\nw=3\n\n[a]\nk=u\n\n[m]\n");
+    }
+
+    #[test]
+    fn display_global_after_sections() {
+        let mut ini = Ini::from_str("[foo]\nb=2").unwrap();
+        ini[""].insert("a".into(), "1".into());
+        assert_eq!(ini.to_string(), "a=1\n\n[foo]\nb=2\n");
+    }
+
+    #[test]
+    fn display_comment() {
+        let mut ini = Ini::from_str("[foo]\nport=80").unwrap();
+        ini["foo"].set_comment("port", "default").unwrap();
+        assert_eq!(ini.to_string(), "[foo]\nport=80 ; default\n");
+    }
+
+    #[test]
+    fn global_section_header() {
+        let text = "[DEFAULT]\na=1\n\n[foo]\nb=2\n";
+        let parse = ParseOptions::new().global_section_header(Some("DEFAULT"));
+        let write = WriteOptions::new().global_section_header(Some("DEFAULT"));
+        let ini = Ini::from_str_with(text, &parse).unwrap();
+        assert_eq!(ini[""]["a"], "1");
+        assert!(ini.section("DEFAULT").is_none());
+        assert_eq!(ini.to_string_with(&write), text);
+        assert_eq!(ini.to_string(), "a=1\n\n[foo]\nb=2\n");
+    }
+
+    #[test]
+    fn spaced_delimiter() {
+        let text = "a=1\n[foo]\nb=\"two words\" ; note\n";
+        let ini = Ini::from_str(text).unwrap();
+        let spaced = ini.to_string_with(&WriteOptions::new().spaced_delimiter(true));
+        assert_eq!(spaced, "a = 1\n\n[foo]\nb = \"two words\" ; note\n");
+        assert_eq!(Ini::from_str(&spaced), Ok(ini.clone()));
+
+        let compact = ini.to_string_with(&WriteOptions::new().spaced_delimiter(false));
+        assert_eq!(compact, ini.to_string());
+        assert_eq!(Ini::from_str(&compact), Ok(ini));
+    }
+
+    #[test]
+    fn sorted() {
+        let text = "b=2\na=1\n\n[zeta]\ny=1\nx=2 ; note\n\n[alpha]\nk=v\n";
+        let ini = Ini::from_str(text).unwrap();
+        let sorted = ini.to_string_with(&WriteOptions::new().sorted(true));
+        assert_eq!(
+            sorted,
+            "a=1\nb=2\n\n[alpha]\nk=v\n\n[zeta]\nx=2 ; note\ny=1\n"
+        );
+        assert_eq!(Ini::from_str(&sorted), Ok(ini.clone()));
+        assert_eq!(ini.to_string(), text);
+    }
+
+    #[test]
+    fn newline() {
+        let ini = Ini::from_str("a=1\n[foo]\nb=\"x\\ny\"\n; end").unwrap();
+        let lf = ini.to_string_with(&WriteOptions::new().newline(Newline::Lf));
+        assert_eq!(lf.as_bytes(), b"a=1\n\n[foo]\nb=\"x\\ny\"\n; end\n");
+        let crlf = ini.to_string_with(&WriteOptions::new().newline(Newline::Crlf));
+        assert_eq!(
+            crlf.as_bytes(),
+            b"a=1\r\n\r\n[foo]\r\nb=\"x\\ny\"\r\n; end\r\n"
+        );
+        assert_eq!(Ini::from_str(&crlf), Ok(ini.clone()));
+        let native = ini.to_string_with(&WriteOptions::new().newline(Newline::Native));
+        assert_eq!(native, if cfg!(windows) { crlf } else { lf });
+    }
+
+    #[test]
+    fn to_string_diff() {
+        let defaults = Ini::from_str("a=1\n[foo]\nb=2\nc=3\n[bar]\nd=4").unwrap();
+        let ini = Ini::from_str("a=1\n[foo]\nb=2\nc=5\n[bar]\n[baz]").unwrap();
+        assert_eq!(ini.to_string_diff(&defaults), "[foo]\nc=5\n\n[baz]\n");
+        assert_eq!(defaults.to_string_diff(&defaults), "");
+    }
+
+    #[test]
+    fn to_string_diff_multiple_values() {
+        let defaults = Ini::from_str("[foo]\npath[]=lib\npath[]=bin\nb=1").unwrap();
+        let ini = Ini::from_str("[foo]\npath[]=src\npath[]=bin\nb=1").unwrap();
+        assert_eq!(
+            ini.to_string_diff(&defaults),
+            "[foo]\npath=src\npath[]=bin\n"
+        );
+        assert_eq!(defaults.to_string_diff(&defaults), "");
+    }
+
+    #[test]
+    fn to_string_diff_fold_case() {
+        let options = ParseOptions::new().case_insensitive(true);
+        let defaults = Ini::from_str_with("[foo]\nA=1 # one", &options).unwrap();
+        let ini = Ini::from_str_with("[FOO]\na=2 # two", &options).unwrap();
+        let diff = ini.to_string_diff(&defaults);
+        assert_eq!(diff, "[FOO]\na=2 # two\n");
+        assert_eq!(
+            Ini::from_str_with(&diff, &options).unwrap()["foo"]["A"],
+            "2"
+        );
+    }
+
+    #[test]
+    fn to_tree_string_truncates() {
+        let mut ini = Ini::new();
+        ini.add_section("foo");
+        ini["foo"].insert("long".into(), "x".repeat(50));
+        let expected = format!("[foo]\n  long = {}...\n", "x".repeat(37));
+        assert_eq!(ini.to_tree_string(), expected);
+    }
+}